@@ -0,0 +1,442 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::Read,
+    path::Path,
+};
+
+use json::JsonValue;
+
+use crate::{
+    direction::Direction,
+    error::TilesetError,
+    grid::Grid,
+    symmetry::{self, SymmetricTile, Symmetry},
+};
+
+/// Reserved neighbor name standing for the outside of the board. Once any tile lists it in a
+/// direction, only tiles that list it may sit on the border on that side.
+pub const EDGE: &str = "edge";
+
+/// Marks an asymmetric socket label as the mirror image of the label without it.
+pub const MIRROR_MARK: char = '\'';
+
+/// The label on one side of a tile, from the optional `sockets` field.
+///
+/// Labels are read going round the tile, so two facing sides read each other back to front.
+/// A label is symmetric unless its mirror (the label with `'` added or removed) appears
+/// somewhere in the tileset; symmetric labels fit the same label, and an asymmetric label
+/// `a` fits only `a'` and vice versa.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Socket {
+    pub label: String,
+    pub symmetric: bool,
+}
+
+impl Socket {
+    /// This socket read back to front.
+    pub fn mirrored(&self) -> Socket {
+        let label = if self.symmetric {
+            self.label.clone()
+        } else if let Some(label) = self.label.strip_suffix(MIRROR_MARK) {
+            label.to_string()
+        } else {
+            format!("{}{}", self.label, MIRROR_MARK)
+        };
+        Socket {
+            label,
+            symmetric: self.symmetric,
+        }
+    }
+
+    /// Whether this socket may face `facing` across a shared side.
+    pub fn fits(&self, facing: &Socket) -> bool {
+        self.mirrored().label == facing.label
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BoardCharacter {
+    pub character: String,
+    pub valid_neighbors: HashMap<Direction, Vec<String>>,
+    /// Socket labels by direction, matched against the facing side of a neighbor in
+    /// addition to `valid_neighbors`.
+    pub sockets: HashMap<Direction, Socket>,
+    /// Relative frequency of this tile, from the optional `weight` field. Defaults to 1.0.
+    pub weight: f64,
+}
+
+impl BoardCharacter {
+    /// Whether `neighbor` may sit in `direction` from this tile. A direction missing from
+    /// `valid_neighbors` allows nothing.
+    pub fn allows(&self, direction: Direction, neighbor: &str) -> bool {
+        self.valid_neighbors
+            .get(&direction)
+            .is_some_and(|list| list.iter().any(|valid_neighbor| valid_neighbor == neighbor))
+    }
+}
+
+pub type AdjacencyMap = HashMap<String, BoardCharacter>;
+
+pub trait WFCAdjacencyMap: Sized {
+    fn create(prototypes_path: &Path) -> Result<Self, TilesetError>;
+    fn parse(source: &str) -> Result<Self, TilesetError>;
+    fn parse_unchecked(source: &str) -> Result<Self, TilesetError>;
+    fn compatible(&self, tile: &str, direction: Direction, neighbor: &str) -> bool;
+    fn compatible_facing(
+        &self,
+        tile: &str,
+        direction: Direction,
+        neighbor: &str,
+        opposite: Direction,
+    ) -> bool;
+    fn grid(&self) -> Grid;
+}
+
+impl WFCAdjacencyMap for AdjacencyMap {
+    fn create(prototypes_path: &Path) -> Result<Self, TilesetError> {
+        let mut buffer = String::new();
+        std::fs::File::open(prototypes_path)
+            .and_then(|mut file| file.read_to_string(&mut buffer))
+            .map_err(|source| TilesetError::Io {
+                path: prototypes_path.to_path_buf(),
+                source,
+            })?;
+        Self::parse(&buffer)
+    }
+
+    fn parse(source: &str) -> Result<Self, TilesetError> {
+        let prototype_map = Self::parse_unchecked(source)?;
+
+        let mut tile_names: Vec<&String> = prototype_map.keys().collect();
+        tile_names.sort();
+        for tile_name in tile_names {
+            let valid_neighbors = &prototype_map[tile_name].valid_neighbors;
+            let mut directions: Vec<&Direction> = valid_neighbors.keys().collect();
+            directions.sort();
+            for direction in directions {
+                let valid_neighbor_list = &valid_neighbors[direction];
+                for (i, reference) in valid_neighbor_list.iter().enumerate() {
+                    if reference != EDGE && !prototype_map.contains_key(reference) {
+                        return Err(TilesetError::UnknownTile {
+                            tile: tile_name.clone(),
+                            path: format!("$.{}.valid_neighbors.{}[{}]", tile_name, direction, i),
+                            reference: reference.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(prototype_map)
+    }
+
+    /// Like `parse`, but keeps neighbor references to tiles that don't exist so they can be
+    /// reported by `validate`.
+    fn parse_unchecked(source: &str) -> Result<Self, TilesetError> {
+        let prototypes_json = json::parse(source)?;
+        if !prototypes_json.is_object() {
+            return Err(TilesetError::WrongType {
+                tile: String::new(),
+                path: "$".to_string(),
+                expected: "object",
+            });
+        }
+
+        let mut prototype_map: AdjacencyMap = HashMap::new();
+        let mut symmetric_tiles = Vec::new();
+
+        for (tile_name, tile_description) in prototypes_json.entries() {
+            let tile_path = format!("$.{}", tile_name);
+            if tile_name == EDGE {
+                return Err(TilesetError::ReservedName {
+                    tile: tile_name.to_string(),
+                    path: tile_path,
+                });
+            }
+            if !tile_description.is_object() {
+                return Err(wrong_type(tile_name, tile_path, "object"));
+            }
+
+            let character = expect_str(tile_name, &tile_path, tile_description, "char")?;
+
+            // A tile described by its sockets doesn't need neighbor lists as well.
+            let valid_neighbors_path = format!("{}.valid_neighbors", tile_path);
+            let no_valid_neighbors = JsonValue::new_object();
+            let valid_neighbors_json = if tile_description.has_key("sockets")
+                && !tile_description.has_key("valid_neighbors")
+            {
+                &no_valid_neighbors
+            } else {
+                expect_field(tile_name, &tile_path, tile_description, "valid_neighbors")?
+            };
+            if !valid_neighbors_json.is_object() {
+                return Err(wrong_type(tile_name, valid_neighbors_path, "object"));
+            }
+
+            let mut valid_neighbors = HashMap::new();
+            for (direction_name, valid_neighbor_list) in valid_neighbors_json.entries() {
+                let direction_path = format!("{}.{}", valid_neighbors_path, direction_name);
+                let direction = expect_direction(tile_name, &direction_path, direction_name)?;
+                if !valid_neighbor_list.is_array() {
+                    return Err(wrong_type(tile_name, direction_path, "array"));
+                }
+
+                let mut list = Vec::new();
+                for (i, valid_neighbor_json) in valid_neighbor_list.members().enumerate() {
+                    match valid_neighbor_json.as_str() {
+                        Some(neighbor) => list.push(neighbor.to_string()),
+                        None => {
+                            let path = format!("{}[{}]", direction_path, i);
+                            return Err(wrong_type(tile_name, path, "string"));
+                        }
+                    }
+                }
+                valid_neighbors.insert(direction, list);
+            }
+
+            let mut sockets = HashMap::new();
+            if tile_description.has_key("sockets") {
+                let sockets_path = format!("{}.sockets", tile_path);
+                let sockets_json = &tile_description["sockets"];
+                if !sockets_json.is_object() {
+                    return Err(wrong_type(tile_name, sockets_path, "object"));
+                }
+                for (direction_name, label) in sockets_json.entries() {
+                    let direction_path = format!("{}.{}", sockets_path, direction_name);
+                    let direction = expect_direction(tile_name, &direction_path, direction_name)?;
+                    let Some(label) = label.as_str() else {
+                        return Err(wrong_type(tile_name, direction_path, "string"));
+                    };
+                    let socket = Socket {
+                        label: label.to_string(),
+                        symmetric: true,
+                    };
+                    sockets.insert(direction, socket);
+                }
+            }
+
+            let weight = if tile_description.has_key("weight") {
+                match tile_description["weight"].as_f64() {
+                    Some(weight) if weight > 0.0 && weight.is_finite() => weight,
+                    _ => {
+                        let path = format!("{}.weight", tile_path);
+                        return Err(wrong_type(tile_name, path, "positive number"));
+                    }
+                }
+            } else {
+                1.0
+            };
+
+            if tile_description.has_key("symmetry") {
+                let symmetry = tile_description["symmetry"]
+                    .as_str()
+                    .and_then(|symmetry| symmetry.parse::<Symmetry>().ok())
+                    .ok_or_else(|| {
+                        let path = format!("{}.symmetry", tile_path);
+                        wrong_type(tile_name, path, "X, I, \\, T, L or F")
+                    })?;
+                let chars = if tile_description.has_key("chars") {
+                    let chars_json = &tile_description["chars"];
+                    let chars: Vec<String> = chars_json
+                        .members()
+                        .filter_map(|c| c.as_str().map(str::to_string))
+                        .collect();
+                    if !chars_json.is_array()
+                        || chars_json.len() != symmetry.cardinality()
+                        || chars.len() != symmetry.cardinality()
+                    {
+                        let path = format!("{}.chars", tile_path);
+                        return Err(wrong_type(tile_name, path, "one string per variant"));
+                    }
+                    Some(chars)
+                } else {
+                    None
+                };
+                symmetric_tiles.push(SymmetricTile {
+                    name: tile_name.to_string(),
+                    symmetry,
+                    chars,
+                });
+            }
+
+            let board_character = BoardCharacter {
+                character: character.to_string(),
+                valid_neighbors,
+                sockets,
+                weight,
+            };
+            prototype_map.insert(tile_name.to_string(), board_character);
+        }
+
+        // A tileset is for a graph, hex grid or 3D grid as soon as it uses a direction only
+        // that grid has, and may then only use directions that exist on it.
+        let grid = prototype_map.grid();
+        let mut tile_names: Vec<&String> = prototype_map.keys().collect();
+        tile_names.sort();
+        for tile_name in tile_names {
+            let tile = &prototype_map[tile_name];
+            for (field, directions) in [
+                (
+                    "valid_neighbors",
+                    tile.valid_neighbors.keys().collect::<Vec<_>>(),
+                ),
+                ("sockets", tile.sockets.keys().collect()),
+            ] {
+                if let Some(direction) = directions
+                    .into_iter()
+                    .find(|&&direction| !grid.has(direction))
+                {
+                    return Err(TilesetError::UnknownDirection {
+                        tile: tile_name.clone(),
+                        path: format!("$.{}.{}.{}", tile_name, field, direction),
+                        direction: direction.to_string(),
+                    });
+                }
+            }
+        }
+        if let (Grid::Hex, Some(symmetric_tile)) = (grid, symmetric_tiles.first()) {
+            let path = format!("$.{}.symmetry", symmetric_tile.name);
+            return Err(wrong_type(
+                &symmetric_tile.name,
+                path,
+                "no symmetry on a hex tile",
+            ));
+        }
+
+        mark_asymmetric_sockets(&mut prototype_map);
+        symmetry::expand(&mut prototype_map, &symmetric_tiles).map_err(|variant| {
+            TilesetError::ReservedName {
+                path: format!("$.{}", variant),
+                tile: variant,
+            }
+        })?;
+
+        Ok(prototype_map)
+    }
+
+    /// Whether `neighbor` may sit in `direction` from `tile`: either both tiles list each
+    /// other on the shared side, or their sockets on it fit each other.
+    fn compatible(&self, tile: &str, direction: Direction, neighbor: &str) -> bool {
+        self.compatible_facing(tile, direction, neighbor, direction.opposite())
+    }
+
+    /// Like `compatible`, for a neighbor that sees `tile` in `opposite`, as a `Graph` with
+    /// its own pairs of labels has it.
+    fn compatible_facing(
+        &self,
+        tile: &str,
+        direction: Direction,
+        neighbor: &str,
+        opposite: Direction,
+    ) -> bool {
+        let sockets_fit = match (
+            self[tile].sockets.get(&direction),
+            self[neighbor].sockets.get(&opposite),
+        ) {
+            (Some(socket), Some(facing)) => socket.fits(facing),
+            _ => false,
+        };
+        sockets_fit
+            || (self[tile].allows(direction, neighbor) && self[neighbor].allows(opposite, tile))
+    }
+
+    /// `Grid::Graph` if any tile has neighbors or sockets `adjacent` to it, or only has them
+    /// in labelled directions, `Grid::Hex` if any has them in a hex-only direction,
+    /// `Grid::Cube` if any has them up or down, otherwise `Grid::Square`.
+    fn grid(&self) -> Grid {
+        let directions: HashSet<Direction> = self
+            .values()
+            .flat_map(|tile| tile.valid_neighbors.keys().chain(tile.sockets.keys()))
+            .copied()
+            .collect();
+        let (labels, sides): (Vec<Direction>, Vec<Direction>) = directions
+            .into_iter()
+            .partition(|direction| matches!(direction, Direction::Label(_)));
+        let beyond = |grid: Grid| {
+            sides
+                .iter()
+                .any(|direction| !grid.directions().contains(direction))
+        };
+        if sides.contains(&Direction::Adjacent) || (sides.is_empty() && !labels.is_empty()) {
+            Grid::Graph
+        } else if beyond(Grid::Square) && beyond(Grid::Cube) {
+            Grid::Hex
+        } else if beyond(Grid::Square) {
+            Grid::Cube
+        } else {
+            Grid::Square
+        }
+    }
+}
+
+/// Marks every socket whose label has a mirrored counterpart somewhere in the tileset as
+/// asymmetric.
+fn mark_asymmetric_sockets(prototype_map: &mut AdjacencyMap) {
+    let mirrored_labels: HashSet<String> = prototype_map
+        .values()
+        .flat_map(|tile| tile.sockets.values())
+        .filter_map(|socket| socket.label.strip_suffix(MIRROR_MARK))
+        .map(str::to_string)
+        .collect();
+    for tile in prototype_map.values_mut() {
+        for socket in tile.sockets.values_mut() {
+            let base = socket
+                .label
+                .strip_suffix(MIRROR_MARK)
+                .unwrap_or(&socket.label);
+            socket.symmetric = !mirrored_labels.contains(base);
+        }
+    }
+}
+
+fn wrong_type(tile: &str, path: String, expected: &'static str) -> TilesetError {
+    TilesetError::WrongType {
+        tile: tile.to_string(),
+        path,
+        expected,
+    }
+}
+
+/// The direction called `direction_name`. Names other than those of `Direction::ALL` are
+/// labels, which only graph tilesets may use.
+fn expect_direction(
+    tile: &str,
+    direction_path: &str,
+    direction_name: &str,
+) -> Result<Direction, TilesetError> {
+    if direction_name.is_empty() {
+        return Err(TilesetError::UnknownDirection {
+            tile: tile.to_string(),
+            path: direction_path.to_string(),
+            direction: direction_name.to_string(),
+        });
+    }
+    Ok(Direction::label(direction_name))
+}
+
+fn expect_field<'a>(
+    tile: &str,
+    tile_path: &str,
+    description: &'a JsonValue,
+    field: &str,
+) -> Result<&'a JsonValue, TilesetError> {
+    if description.has_key(field) {
+        Ok(&description[field])
+    } else {
+        Err(TilesetError::MissingField {
+            tile: tile.to_string(),
+            path: format!("{}.{}", tile_path, field),
+        })
+    }
+}
+
+fn expect_str<'a>(
+    tile: &str,
+    tile_path: &str,
+    description: &'a JsonValue,
+    field: &str,
+) -> Result<&'a str, TilesetError> {
+    expect_field(tile, tile_path, description, field)?
+        .as_str()
+        .ok_or_else(|| wrong_type(tile, format!("{}.{}", tile_path, field), "string"))
+}
@@ -0,0 +1,89 @@
+/// A fixed-width set of tile ids. Every domain of a board has the same number of words, chosen
+/// from the tileset size when the board is created.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    words: Box<[u64]>,
+}
+
+pub fn words_for(len: usize) -> usize {
+    len.div_ceil(64).max(1)
+}
+
+impl BitSet {
+    /// An empty set able to hold ids `0..len`.
+    pub fn new(len: usize) -> Self {
+        BitSet {
+            words: vec![0; words_for(len)].into_boxed_slice(),
+        }
+    }
+
+    /// The set of all ids `0..len`.
+    pub fn full(len: usize) -> Self {
+        let mut set = BitSet::new(len);
+        for id in 0..len {
+            set.insert(id);
+        }
+        set
+    }
+
+    pub fn from_words(words: &[u64]) -> Self {
+        BitSet {
+            words: words.into(),
+        }
+    }
+
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    pub fn insert(&mut self, id: usize) {
+        self.words[id / 64] |= 1 << (id % 64);
+    }
+
+    pub fn remove(&mut self, id: usize) {
+        self.words[id / 64] &= !(1 << (id % 64));
+    }
+
+    pub fn contains(&self, id: usize) -> bool {
+        contains(&self.words, id)
+    }
+
+    pub fn len(&self) -> usize {
+        count(&self.words)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        is_empty(&self.words)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        ones(&self.words)
+    }
+}
+
+pub fn contains(words: &[u64], id: usize) -> bool {
+    words[id / 64] & (1 << (id % 64)) != 0
+}
+
+pub fn count(words: &[u64]) -> usize {
+    words.iter().map(|word| word.count_ones() as usize).sum()
+}
+
+pub fn is_empty(words: &[u64]) -> bool {
+    words.iter().all(|&word| word == 0)
+}
+
+/// The ids set in `words`, in increasing order.
+pub fn ones(words: &[u64]) -> impl Iterator<Item = usize> + '_ {
+    words.iter().enumerate().flat_map(|(i, &word)| {
+        let mut word = word;
+        std::iter::from_fn(move || {
+            if word == 0 {
+                return None;
+            }
+            let bit = word.trailing_zeros() as usize;
+            word &= word - 1;
+            Some(i * 64 + bit)
+        })
+    })
+}
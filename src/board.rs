@@ -0,0 +1,776 @@
+use std::{collections::VecDeque, time::Duration};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    adjacency::{AdjacencyMap, WFCAdjacencyMap},
+    bitset::{self, BitSet},
+    error::SeedError,
+    grid::{Grid, Lattice},
+    heuristic::{CellQueue, Heuristic},
+    mask::Mask,
+    rules::{Rules, TileId},
+    topology::Topology,
+};
+
+/// A board position: `[x, y]` in screen coordinates on square boards and `[q, r]` in axial
+/// coordinates on hex boards; see `Direction`.
+pub type Vec2 = [isize; 2];
+/// A position on a 3D board: `[x, y, z]`, with `[x, y]` placed as on a square board and z
+/// counting layers from the bottom.
+pub type Vec3 = [isize; 3];
+pub type Domain = Vec<String>;
+
+/// A snapshot of one cell, as returned by `Board::get`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Tile {
+    Collapsed(String),
+    Uncollapsed(Domain),
+}
+
+/// A weighted random permutation of `domain`, ordered so that popping from the end yields
+/// heavier tiles first more often (Efraimidis-Spirakis: sort by `u^(1/weight)`).
+fn weighted_order(rules: &Rules, domain: &[u64], rng: &mut StdRng) -> Vec<TileId> {
+    let mut keyed: Vec<(f64, TileId)> = bitset::ones(domain)
+        .map(|tile| (rng.gen::<f64>().powf(1.0 / rules.weight(tile)), tile))
+        .collect();
+    keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+    keyed.into_iter().map(|(_key, tile)| tile).collect()
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CollapseOptions {
+    /// Seeds every random decision, so the same seed, tileset and size give the same board.
+    pub seed: u64,
+    pub heuristic: Heuristic,
+    /// Redraw the board in the terminal after every step. Much slower; off by default.
+    pub animate: bool,
+    /// Give up after undoing this many candidates, as if there were no solution. By default
+    /// the search runs until it finds a solution or rules them all out, which can take very
+    /// long on hard tilesets.
+    pub max_backtracks: Option<usize>,
+}
+
+/// How many candidates `collapse` may undo without collapsing more cells than ever before,
+/// before it stops backtracking and repairs the board instead; see `Repair`.
+const STUCK_BACKTRACKS: usize = 64;
+
+/// How far around the way out of a pocket `collapse` first resets the board.
+const REPAIR_RADIUS: usize = 1;
+
+/// How `collapse` gets out of contradictions that backtracking doesn't, usually because the
+/// search has closed off a pocket of cells that no tiles fit however it is filled. The cells on
+/// the shortest way out of the pocket, and within a radius of it, are reset to how they were
+/// before the search, and every other cell is kept as it is. Each contradiction among the cells
+/// reset last widens the radius by a step, until a reset takes in the whole board; after that,
+/// the search only backtracks.
+struct Repair {
+    /// The domains and collapsed cells before the search.
+    start: Vec<u64>,
+    fixed: Vec<bool>,
+    /// The cells reset last, unless that was the whole board.
+    region: Vec<bool>,
+    /// How far the next reset reaches, or `None` once the whole board was reset.
+    radius: Option<usize>,
+}
+
+/// Tiles removed from cells' domains, so they can be put back when backtracking.
+type Trail = Vec<(usize, BitSet)>;
+
+/// A cell collapsed during search, with what's needed to undo it and try the next candidate.
+struct Decision {
+    cell: usize,
+    /// The candidate the cell is collapsed to, until it is undone.
+    tile: Option<TileId>,
+    remaining: Vec<TileId>,
+    trail: Trail,
+}
+
+/// The cells of a board, each with a domain: a bitset over the tileset's interned tile ids,
+/// stored back to back in `domains`. Which cells neighbor each other is up to the topology,
+/// a `Lattice` unless the board is made with `Board::new`.
+pub struct Board<T = Lattice> {
+    topology: T,
+    rules: Rules,
+    domains: Vec<u64>,
+    collapsed: Vec<bool>,
+    /// Scratch space for `propagate`: whether a cell is waiting in its queue.
+    pending: Vec<bool>,
+}
+
+impl Board {
+    /// A board of `width` by `height` cells, on the grid the tileset is written for.
+    ///
+    /// Panics if that is `Grid::Graph`; graph tilesets go on a `Graph` with `Board::new`.
+    pub fn create(prototype_map: &AdjacencyMap, width: isize, height: isize) -> Self {
+        Board::create_3d(prototype_map, width, height, 1)
+    }
+
+    /// A board of `depth` layers of `width` by `height` cells. Layers are only neighbors on
+    /// the 3D grid of a tileset with up and down rules; otherwise each is solved on its own.
+    pub fn create_3d(
+        prototype_map: &AdjacencyMap,
+        width: isize,
+        height: isize,
+        depth: isize,
+    ) -> Self {
+        let lattice = Lattice::new(prototype_map.grid(), width, height, depth);
+        Board::new(prototype_map, lattice)
+    }
+
+    /// See `Lattice::with_periodic`.
+    pub fn with_periodic(mut self, periodic_x: bool, periodic_y: bool) -> Self {
+        self.topology = self.topology.with_periodic(periodic_x, periodic_y);
+        self
+    }
+
+    /// See `Lattice::with_mask`.
+    pub fn with_mask(mut self, mask: &Mask) -> Self {
+        self.topology = self.topology.with_mask(mask);
+        self
+    }
+
+    pub fn grid(&self) -> Grid {
+        self.topology.grid()
+    }
+
+    pub fn width(&self) -> isize {
+        self.topology.width()
+    }
+
+    pub fn height(&self) -> isize {
+        self.topology.height()
+    }
+
+    /// The number of layers; 1 unless created with `create_3d`.
+    pub fn depth(&self) -> isize {
+        self.topology.depth()
+    }
+
+    /// The cell at `pos` on the bottom layer.
+    pub fn get(&self, pos: &Vec2) -> Option<Tile> {
+        let [x, y] = *pos;
+        self.get_voxel(&[x, y, 0])
+    }
+
+    /// The cell at `pos` on any layer.
+    pub fn get_voxel(&self, pos: &Vec3) -> Option<Tile> {
+        self.get_cell(self.topology.cell(pos)?)
+    }
+
+    /// Applies a partial board: each seed fixes its cell to one tile or narrows it to a set
+    /// of tiles, and is propagated (along with the tileset's edge rules) before the next one.
+    /// Seeds are permanent; `collapse` fills in the rest around them.
+    ///
+    /// On error the board is left partially seeded and should be discarded.
+    pub fn seed(&mut self, seeds: &[(Vec3, Tile)]) -> Result<(), SeedError> {
+        // Nothing is solved yet, so the queue and rng only exist to satisfy `propagate`.
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut queue = CellQueue::new(Heuristic::default(), self.collapsed.len());
+        let mut trail = Trail::new();
+
+        self.constrain_edges(&mut queue, &mut rng).map_err(|cell| {
+            SeedError::EdgeContradiction {
+                cell: self.topology.pos(cell),
+            }
+        })?;
+
+        for (pos, tile) in seeds {
+            let cell = self
+                .topology
+                .cell(pos)
+                .ok_or(SeedError::OutOfBounds { pos: *pos })?;
+            let names = match tile {
+                Tile::Collapsed(name) => std::slice::from_ref(name),
+                Tile::Uncollapsed(domain) => domain.as_slice(),
+            };
+            let mut allowed = BitSet::new(self.rules.len());
+            for name in names {
+                let id = self.rules.id(name).ok_or_else(|| SeedError::UnknownTile {
+                    pos: *pos,
+                    tile: name.clone(),
+                })?;
+                allowed.insert(id);
+            }
+            if allowed.is_empty() {
+                return Err(SeedError::EmptyDomain { pos: *pos });
+            }
+
+            self.restrict(cell, allowed.words(), &mut trail);
+            if matches!(tile, Tile::Collapsed(_)) {
+                self.collapsed[cell] = true;
+            }
+            let result = if bitset::is_empty(self.domain(cell)) {
+                Err(cell)
+            } else {
+                self.propagate(cell, &mut trail, &mut queue, &mut rng)
+            };
+            result.map_err(|emptied| SeedError::Contradiction {
+                pos: *pos,
+                cell: self.topology.pos(emptied),
+            })?;
+        }
+        Ok(())
+    }
+
+    /// The board as a voxel list: a header line with the board's size, then one
+    /// `x y z tile` line per collapsed cell, in storage order. Uncollapsed and masked cells
+    /// are left out, so the list describes a solid the way sparse voxel formats do.
+    pub fn render_voxels(&self) -> String {
+        let lattice = &self.topology;
+        let mut output = format!(
+            "# {} {} {}\n",
+            lattice.width(),
+            lattice.height(),
+            lattice.depth()
+        );
+        for cell in self.cells().filter(|&cell| self.collapsed[cell]) {
+            let [x, y, z] = lattice.pos(cell);
+            let tile = bitset::ones(self.domain(cell)).next().unwrap();
+            output.push_str(&format!("{} {} {} {}\n", x, y, z, self.rules.name(tile)));
+        }
+        output
+    }
+
+    /// The board as an SVG image: a square or hexagon per cell with its character inside.
+    /// The layers of a 3D board are drawn one below the other, bottom layer first.
+    pub fn render_svg(&self) -> String {
+        const SIZE: f64 = 24.0;
+        let lattice = &self.topology;
+        let hex_width = SIZE * 3f64.sqrt() / 2.0;
+        let (image_width, layer_height) = match lattice.grid() {
+            Grid::Hex => (
+                (lattice.width() as f64 + 0.5) * hex_width,
+                (lattice.height() as f64 * 0.75 + 0.25) * SIZE,
+            ),
+            _ => (
+                lattice.width() as f64 * SIZE,
+                lattice.height() as f64 * SIZE,
+            ),
+        };
+        // Layers are a cell's height apart.
+        let layer_spacing = layer_height + SIZE;
+        let image_height = layer_spacing * lattice.depth() as f64 - SIZE;
+
+        let mut output = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" \
+             font-family=\"monospace\" font-size=\"{:.0}\" text-anchor=\"middle\" \
+             dominant-baseline=\"central\">\n",
+            image_width.ceil(),
+            image_height.ceil(),
+            SIZE * 0.6
+        );
+        for cell in self.cells() {
+            let [column, row, layer] = lattice.storage(cell);
+            let (x, y) = match lattice.grid() {
+                Grid::Hex => (
+                    (column as f64 + 0.5 + 0.5 * (row % 2) as f64) * hex_width,
+                    (row as f64 * 0.75 + 0.5) * SIZE,
+                ),
+                _ => ((column as f64 + 0.5) * SIZE, (row as f64 + 0.5) * SIZE),
+            };
+            let y = y + layer as f64 * layer_spacing;
+            let corners: Vec<(f64, f64)> = match lattice.grid() {
+                Grid::Hex => (0..6)
+                    .map(|corner| {
+                        let angle =
+                            std::f64::consts::PI / 3.0 * corner as f64 - std::f64::consts::PI / 6.0;
+                        (x + SIZE / 2.0 * angle.cos(), y + SIZE / 2.0 * angle.sin())
+                    })
+                    .collect(),
+                _ => [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)]
+                    .map(|(dx, dy)| (x + dx * SIZE, y + dy * SIZE))
+                    .to_vec(),
+            };
+            let points: Vec<String> = corners
+                .iter()
+                .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+                .collect();
+            output.push_str(&format!(
+                "  <polygon points=\"{}\" fill=\"white\" stroke=\"gray\"/>\n",
+                points.join(" ")
+            ));
+            output.push_str(&format!(
+                "  <text x=\"{:.1}\" y=\"{:.1}\">{}</text>\n",
+                x,
+                y,
+                escape_xml(self.character(cell))
+            ));
+        }
+        output.push_str("</svg>\n");
+        output
+    }
+}
+
+impl<T: Topology> Board<T> {
+    /// A board with a cell for every cell of `topology`, each allowing the whole tileset.
+    pub fn new(prototype_map: &AdjacencyMap, topology: T) -> Self {
+        let rules = Rules::new(prototype_map, &topology);
+        Board::from_rules(rules, topology)
+    }
+
+    /// Like `new`, with the tileset already compiled for `topology`, so that boards of the same
+    /// tileset don't each compile it again.
+    pub fn from_rules(rules: Rules, topology: T) -> Self {
+        let cells = topology.len();
+        let full = BitSet::full(rules.len());
+        Board {
+            topology,
+            domains: full.words().repeat(cells),
+            collapsed: vec![false; cells],
+            pending: vec![false; cells],
+            rules,
+        }
+    }
+
+    pub fn topology(&self) -> &T {
+        &self.topology
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// The cell numbered `cell` in the topology, unless it isn't part of the board.
+    pub fn get_cell(&self, cell: usize) -> Option<Tile> {
+        if cell >= self.topology.len() || !self.topology.contains(cell) {
+            return None;
+        }
+        let mut names =
+            bitset::ones(self.domain(cell)).map(|tile| self.rules.name(tile).to_string());
+        if self.collapsed[cell] {
+            names.next().map(Tile::Collapsed)
+        } else {
+            Some(Tile::Uncollapsed(names.collect()))
+        }
+    }
+
+    pub fn is_collapsed(&self) -> bool {
+        self.cells().all(|cell| self.collapsed[cell])
+    }
+
+    /// Every cell that is part of the board.
+    fn cells(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.topology.len()).filter(|&cell| self.topology.contains(cell))
+    }
+
+    pub(crate) fn domain(&self, cell: usize) -> &[u64] {
+        let words = self.rules.words();
+        &self.domains[cell * words..(cell + 1) * words]
+    }
+
+    pub(crate) fn is_cell_collapsed(&self, cell: usize) -> bool {
+        self.collapsed[cell]
+    }
+
+    /// Narrows `cell` to the tiles in `allowed`, recording what was removed in `trail`.
+    /// Returns whether anything changed.
+    fn restrict(&mut self, cell: usize, allowed: &[u64], trail: &mut Trail) -> bool {
+        let words = self.rules.words();
+        let domain = &mut self.domains[cell * words..(cell + 1) * words];
+        if domain
+            .iter()
+            .zip(allowed)
+            .all(|(word, allowed)| word & !allowed == 0)
+        {
+            return false;
+        }
+
+        let removed: Vec<u64> = domain
+            .iter()
+            .zip(allowed)
+            .map(|(word, allowed)| word & !allowed)
+            .collect();
+        for (word, allowed) in domain.iter_mut().zip(allowed) {
+            *word &= allowed;
+        }
+        trail.push((cell, BitSet::from_words(&removed)));
+        true
+    }
+
+    fn restore_domains(&mut self, trail: Trail, queue: &mut CellQueue, rng: &mut StdRng) {
+        let words = self.rules.words();
+        for (cell, removed) in trail.into_iter().rev() {
+            let domain = &mut self.domains[cell * words..(cell + 1) * words];
+            for (word, removed) in domain.iter_mut().zip(removed.words()) {
+                *word |= removed;
+            }
+            queue.push(self, cell, rng);
+        }
+    }
+
+    /// Prunes domains outwards from `cell` until every remaining tile has a compatible tile in
+    /// each neighboring cell. Fails with the first cell whose domain becomes empty.
+    fn propagate(
+        &mut self,
+        cell: usize,
+        trail: &mut Trail,
+        queue: &mut CellQueue,
+        rng: &mut StdRng,
+    ) -> Result<(), usize> {
+        let mut pending = VecDeque::from([cell]);
+        self.pending[cell] = true;
+        let mut allowed = vec![0; self.rules.words()];
+        let mut result = Ok(());
+
+        'propagate: while let Some(cell) = pending.pop_front() {
+            self.pending[cell] = false;
+            let neighbors = self.topology.neighbors(cell);
+            for (direction, neighbor) in neighbors {
+                // A cell wrapped around onto itself is its own neighbor, and has to fit itself
+                // even once collapsed.
+                if self.collapsed[neighbor] && neighbor != cell {
+                    continue;
+                }
+
+                self.rules
+                    .support(direction, self.domain(cell), &mut allowed);
+                if !self.restrict(neighbor, &allowed, trail) {
+                    continue;
+                }
+                if bitset::is_empty(self.domain(neighbor)) {
+                    result = Err(neighbor);
+                    break 'propagate;
+                }
+                queue.push(self, neighbor, rng);
+                if !self.pending[neighbor] {
+                    self.pending[neighbor] = true;
+                    pending.push_back(neighbor);
+                }
+            }
+        }
+
+        for cell in pending {
+            self.pending[cell] = false;
+        }
+        result
+    }
+
+    /// Restricts every border cell to the tiles the tileset allows next to the edge on that
+    /// side, and propagates the result. These restrictions are never undone. Fails with the
+    /// first cell the edge rules leave without candidates.
+    fn constrain_edges(&mut self, queue: &mut CellQueue, rng: &mut StdRng) -> Result<(), usize> {
+        let mut trail = Trail::new();
+        let cells: Vec<usize> = self.cells().collect();
+        for cell in cells {
+            let mut outside = self.topology.directions().to_vec();
+            for (direction, _neighbor) in self.topology.neighbors(cell) {
+                outside.retain(|&other| other != direction);
+            }
+
+            let mut allowed = BitSet::full(self.rules.len());
+            for direction in outside {
+                if let Some(edge) = self.rules.edge(direction) {
+                    for tile in 0..self.rules.len() {
+                        if !edge.contains(tile) {
+                            allowed.remove(tile);
+                        }
+                    }
+                }
+            }
+
+            if !self.restrict(cell, allowed.words(), &mut trail) {
+                continue;
+            }
+            if bitset::is_empty(self.domain(cell)) {
+                return Err(cell);
+            }
+            queue.push(self, cell, rng);
+            self.propagate(cell, &mut trail, queue, rng)?;
+        }
+        Ok(())
+    }
+
+    /// Resets the cells within `radius` steps of `centers` to their domains in `start`, keeping
+    /// every other cell as it is, and propagates into them from around. If that reaches every
+    /// cell connected to `centers`, the whole board is reset instead. Returns the cells reset,
+    /// or `None` if that was the whole board.
+    fn reset(
+        &mut self,
+        centers: &[usize],
+        radius: usize,
+        start: &[u64],
+        fixed: &[bool],
+        queue: &mut CellQueue,
+        rng: &mut StdRng,
+    ) -> (Option<Vec<bool>>, Result<(), usize>) {
+        let mut region = centers.to_vec();
+        let mut reached = vec![false; self.topology.len()];
+        for &center in centers {
+            reached[center] = true;
+        }
+        let mut ring = 0..region.len();
+        for _ in 0..radius {
+            for i in ring.clone() {
+                for (_direction, neighbor) in self.topology.neighbors(region[i]) {
+                    if !reached[neighbor] {
+                        reached[neighbor] = true;
+                        region.push(neighbor);
+                    }
+                }
+            }
+            ring = ring.end..region.len();
+        }
+        let whole = ring.is_empty();
+        if whole {
+            region = self.cells().collect();
+        } else {
+            // Uncollapsed cells just outside may have lost tiles to the cells being reset.
+            for i in ring {
+                for (_direction, neighbor) in self.topology.neighbors(region[i]) {
+                    if !reached[neighbor] && !self.collapsed[neighbor] {
+                        reached[neighbor] = true;
+                        region.push(neighbor);
+                    }
+                }
+            }
+        }
+
+        let words = self.rules.words();
+        for &cell in &region {
+            self.domains[cell * words..(cell + 1) * words]
+                .copy_from_slice(&start[cell * words..(cell + 1) * words]);
+            self.collapsed[cell] = fixed[cell];
+        }
+        let mut trail = Trail::new();
+        for &cell in &region {
+            if !self.collapsed[cell] {
+                queue.push(self, cell, rng);
+            }
+            for (_direction, neighbor) in self.topology.neighbors(cell) {
+                if let Err(emptied) = self.propagate(neighbor, &mut trail, queue, rng) {
+                    return ((!whole).then_some(reached), Err(emptied));
+                }
+            }
+        }
+        ((!whole).then_some(reached), Ok(()))
+    }
+
+    /// The shortest path from `center` to the edge of the board or to an uncollapsed cell that
+    /// isn't closed off together with it. A pocket of cells closed off by collapsed ones can be
+    /// impossible to fill however they are reset, as long as they stay closed off.
+    fn path_to_open(&self, center: usize) -> Vec<usize> {
+        let mut pocket = vec![false; self.topology.len()];
+        pocket[center] = true;
+        let mut cells = vec![center];
+        while let Some(cell) = cells.pop() {
+            for (_direction, neighbor) in self.topology.neighbors(cell) {
+                if !pocket[neighbor] && !self.collapsed[neighbor] {
+                    pocket[neighbor] = true;
+                    cells.push(neighbor);
+                }
+            }
+        }
+
+        let mut previous = vec![usize::MAX; self.topology.len()];
+        previous[center] = center;
+        let mut pending = VecDeque::from([center]);
+        while let Some(cell) = pending.pop_front() {
+            let neighbors = self.topology.neighbors(cell);
+            if neighbors.len() < self.topology.directions().len()
+                || (!self.collapsed[cell] && !pocket[cell])
+            {
+                let mut path = vec![cell];
+                while path[path.len() - 1] != center {
+                    path.push(previous[path[path.len() - 1]]);
+                }
+                return path;
+            }
+            for (_direction, neighbor) in neighbors {
+                if previous[neighbor] == usize::MAX {
+                    previous[neighbor] = cell;
+                    pending.push_back(neighbor);
+                }
+            }
+        }
+        vec![center]
+    }
+
+    /// Resets the board on the way out of the pocket around `center` until that propagates;
+    /// see `Repair`. Returns false if even resetting the whole board fails, or if it already
+    /// had been.
+    fn repair(
+        &mut self,
+        center: usize,
+        repair: &mut Repair,
+        queue: &mut CellQueue,
+        rng: &mut StdRng,
+    ) -> bool {
+        let Some(mut radius) = repair.radius else {
+            return false;
+        };
+        if !repair.region.get(center).copied().unwrap_or(false) {
+            radius = REPAIR_RADIUS;
+        }
+        let path = self.path_to_open(center);
+        loop {
+            let (region, result) =
+                self.reset(&path, radius, &repair.start, &repair.fixed, queue, rng);
+            radius += 1;
+            repair.radius = region.is_some().then_some(radius);
+            repair.region = region.unwrap_or_default();
+            if result.is_ok() || repair.radius.is_none() {
+                return result.is_ok();
+            }
+        }
+    }
+
+    /// Fills in every cell, picking cells by `options.heuristic` and tiles at random by weight,
+    /// and returns whether that succeeded. Contradictions are backtracked out of one candidate
+    /// at a time, and when that gets nowhere, the board is repaired around them; see `Repair`.
+    pub fn collapse(&mut self, options: &CollapseOptions) -> bool {
+        let mut rng = StdRng::seed_from_u64(options.seed);
+        let mut queue = CellQueue::new(options.heuristic, self.collapsed.len());
+        let cells: Vec<usize> = self.cells().collect();
+        for cell in cells {
+            if !self.collapsed[cell] {
+                queue.push(self, cell, &mut rng);
+            }
+        }
+        if self.constrain_edges(&mut queue, &mut rng).is_err() {
+            return false;
+        }
+        let mut repair = Repair {
+            start: self.domains.clone(),
+            fixed: self.collapsed.clone(),
+            region: Vec::new(),
+            radius: Some(REPAIR_RADIUS),
+        };
+
+        let mut stack: Vec<Decision> = Vec::new();
+        // Tiles ruled out before the first decision on the stack, which are never undone.
+        let mut settled = Trail::new();
+        let mut backtracks = 0;
+        // Cells collapsed before the first decision on the stack, and the most cells ever
+        // collapsed at once.
+        let mut kept = 0;
+        let mut deepest = 0;
+        let mut stuck = 0;
+
+        loop {
+            if options.animate {
+                self.print();
+            }
+            if kept + stack.len() > deepest {
+                deepest = kept + stack.len();
+                stuck = 0;
+            }
+            let Some(cell) = queue.pop(self) else {
+                return true;
+            };
+
+            let remaining = weighted_order(&self.rules, self.domain(cell), &mut rng);
+            stack.push(Decision {
+                cell,
+                tile: None,
+                remaining,
+                trail: Vec::new(),
+            });
+
+            // Try the next candidate of the newest decision, popping exhausted decisions until
+            // one of them propagates without a contradiction.
+            let mut center = cell;
+            loop {
+                let Some(mut decision) = stack.pop() else {
+                    if !self.repair(center, &mut repair, &mut queue, &mut rng) {
+                        return false;
+                    }
+                    kept = self.cells().filter(|&cell| self.collapsed[cell]).count();
+                    break;
+                };
+
+                self.collapsed[decision.cell] = false;
+                self.restore_domains(std::mem::take(&mut decision.trail), &mut queue, &mut rng);
+
+                // The candidate just undone fails whatever is decided after it, so it is ruled
+                // out under the earlier decisions, and the cells around learn from it too. If
+                // that is a contradiction, the previous decision fails as well.
+                if let Some(failed) = decision.tile.take() {
+                    backtracks += 1;
+                    if options.max_backtracks.is_some_and(|max| backtracks > max) {
+                        return false;
+                    }
+                    stuck += 1;
+                    if stuck > STUCK_BACKTRACKS && repair.radius.is_some() {
+                        // Keep the decisions still standing, and repair around them.
+                        stuck = 0;
+                        stack.clear();
+                        queue.push(self, decision.cell, &mut rng);
+                        continue;
+                    }
+
+                    let trail = match stack.last_mut() {
+                        Some(parent) => &mut parent.trail,
+                        None => &mut settled,
+                    };
+                    let mut allowed = BitSet::full(self.rules.len());
+                    allowed.remove(failed);
+                    self.restrict(decision.cell, allowed.words(), trail);
+                    let result = if bitset::is_empty(self.domain(decision.cell)) {
+                        Err(decision.cell)
+                    } else {
+                        self.propagate(decision.cell, trail, &mut queue, &mut rng)
+                    };
+                    if let Err(emptied) = result {
+                        center = emptied;
+                        queue.push(self, decision.cell, &mut rng);
+                        continue;
+                    }
+                }
+
+                // Candidates may have been ruled out since the decision was made.
+                let domain = self.domain(decision.cell);
+                decision
+                    .remaining
+                    .retain(|&tile| bitset::contains(domain, tile));
+                let Some(possible_tile) = decision.remaining.pop() else {
+                    queue.push(self, decision.cell, &mut rng);
+                    continue;
+                };
+
+                let mut only = BitSet::new(self.rules.len());
+                only.insert(possible_tile);
+                self.restrict(decision.cell, only.words(), &mut decision.trail);
+                self.collapsed[decision.cell] = true;
+                decision.tile = Some(possible_tile);
+                let result =
+                    self.propagate(decision.cell, &mut decision.trail, &mut queue, &mut rng);
+                stack.push(decision);
+                match result {
+                    Ok(()) => break,
+                    Err(emptied) => center = emptied,
+                }
+            }
+        }
+    }
+
+    /// The character drawn for `cell`: its tile's, or `.` while uncollapsed.
+    fn character(&self, cell: usize) -> &str {
+        if self.collapsed[cell] {
+            let tile = bitset::ones(self.domain(cell)).next().unwrap();
+            self.rules.character(tile)
+        } else {
+            "."
+        }
+    }
+
+    /// The board as text, laid out by the topology; see `Topology::render`.
+    pub fn render(&self) -> String {
+        let characters: Vec<&str> = (0..self.topology.len())
+            .map(|cell| self.character(cell))
+            .collect();
+        self.topology.render(&characters)
+    }
+
+    pub fn print(&self) {
+        std::thread::sleep(Duration::from_millis(10));
+        print!("\x1B[2J\x1B[1;1H");
+        print!("{}", self.render());
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+
+use crate::{
+    adjacency::{AdjacencyMap, WFCAdjacencyMap, EDGE},
+    bitset::BitSet,
+    board::{Board, CollapseOptions, Tile, Vec2},
+    error::ChunkError,
+    grid::{Grid, Lattice},
+    heuristic::Heuristic,
+    mask::Mask,
+    rules::{Rules, TileId},
+};
+
+/// How many times a chunk is collapsed, each time with a new seed, before giving up on it.
+const ATTEMPTS: usize = 8;
+/// How many candidates one attempt at a chunk may undo, per cell solved with it.
+const BACKTRACKS_PER_CELL: usize = 4;
+/// How far a chunk's margin reaches into its neighbors from later rounds; see `World`.
+const MARGIN: isize = 3;
+
+/// An unbounded square world, generated on demand in chunks of `width` by `height` cells.
+/// Chunks are laid like bricks: chunk `[cx, cy]` covers the cells from
+/// `[cx * width, cy * height]`, in screen coordinates like a board, shifted right by half a
+/// chunk on odd rows.
+///
+/// Every chunk is a function of the world seed and its coordinates alone, so chunks can be
+/// generated, forgotten and generated again in any order. To keep seams consistent, chunks
+/// are solved in three rounds, colored so that chunks of the same round never touch: the
+/// first round is solved on its own, and the later ones against the borders of their
+/// neighbors from earlier rounds, which are generated first if needed. Where chunks meet,
+/// three meet, so a chunk from the last round only ever fills in next to neighbors that
+/// already agree with each other.
+///
+/// Whether a chunk can be filled in between its neighbors depends on all of them at once:
+/// pipes that form closed loops, for instance, need an even number of ends to cross into it.
+/// So each chunk is solved together with a margin reaching `MARGIN` cells into its neighbors
+/// from later rounds, which has to close off against a background tile, one that fits next to
+/// itself on every side like empty space. The margin is thrown away afterwards, but shows that
+/// a later chunk can always be filled in: with the margins of its neighbors, which never
+/// touch, and background in between. Tilesets without a background tile are solved without
+/// margins, and have to be able to fill a chunk between any borders its neighbors end up
+/// with.
+pub struct World {
+    /// The tileset without its edge rules.
+    rules: Rules,
+    /// The first tile that fits next to itself on every side, if any.
+    background: Option<TileId>,
+    width: isize,
+    height: isize,
+    seed: u64,
+    heuristic: Heuristic,
+    chunks: HashMap<Vec2, Board>,
+}
+
+impl World {
+    /// A world of chunks of `width` by `height` cells, both at least 1. A world has no edge,
+    /// so the tileset's edge rules are ignored. Only square tilesets can be chunked.
+    pub fn new(
+        prototype_map: &AdjacencyMap,
+        width: isize,
+        height: isize,
+        seed: u64,
+    ) -> Result<Self, ChunkError> {
+        if width < 1 || height < 1 {
+            return Err(ChunkError::InvalidSize { width, height });
+        }
+        let grid = prototype_map.grid();
+        if grid != Grid::Square {
+            return Err(ChunkError::UnsupportedGrid(grid));
+        }
+
+        let mut prototype_map = prototype_map.clone();
+        for tile in prototype_map.values_mut() {
+            for valid_neighbor_list in tile.valid_neighbors.values_mut() {
+                valid_neighbor_list.retain(|valid_neighbor| valid_neighbor != EDGE);
+            }
+        }
+        let rules = Rules::new(&prototype_map, &Lattice::new(grid, width, height, 1));
+        let background = (0..rules.len()).find(|&tile| {
+            grid.directions()
+                .iter()
+                .all(|&direction| rules.compatible(direction, tile).contains(tile))
+        });
+        Ok(World {
+            rules,
+            background,
+            width,
+            height,
+            seed,
+            heuristic: Heuristic::default(),
+            chunks: HashMap::new(),
+        })
+    }
+
+    pub fn with_heuristic(mut self, heuristic: Heuristic) -> Self {
+        self.heuristic = heuristic;
+        self
+    }
+
+    pub fn width(&self) -> isize {
+        self.width
+    }
+
+    pub fn height(&self) -> isize {
+        self.height
+    }
+
+    /// The seed `chunk` is first collapsed with: the world seed and the chunk's coordinates,
+    /// mixed with SplitMix64 so that neighboring chunks get unrelated seeds. Should that
+    /// attempt backtrack too much, the chunk is collapsed again with the seed mixed once more,
+    /// and so on, up to `ATTEMPTS` times.
+    pub fn chunk_seed(&self, chunk: Vec2) -> u64 {
+        let [cx, cy] = chunk;
+        [cx as u64, cy as u64]
+            .into_iter()
+            .fold(splitmix64(self.seed), |hash, coordinate| {
+                splitmix64(hash ^ coordinate)
+            })
+    }
+
+    /// The chunk containing the cell at `pos`, and the cell's position within it.
+    pub fn locate(&self, pos: &Vec2) -> (Vec2, Vec2) {
+        let [x, y] = *pos;
+        let cy = y.div_euclid(self.height);
+        let x = x - self.shift(cy);
+        (
+            [x.div_euclid(self.width), cy],
+            [x.rem_euclid(self.width), y.rem_euclid(self.height)],
+        )
+    }
+
+    /// The position of the top-left cell of `chunk`.
+    pub fn origin(&self, chunk: Vec2) -> Vec2 {
+        let [cx, cy] = chunk;
+        [cx * self.width + self.shift(cy), cy * self.height]
+    }
+
+    /// How far the chunks of row `cy` are shifted right.
+    fn shift(&self, cy: isize) -> isize {
+        (cy & 1) * (self.width / 2)
+    }
+
+    /// The round `chunk` is solved in. Bricks touch like the hexagons of an "odd-r" hex
+    /// grid, which axial coordinates color with three colors.
+    fn round(chunk: Vec2) -> isize {
+        let [q, r] = Grid::Hex.from_storage(chunk);
+        (q - r).rem_euclid(3)
+    }
+
+    /// The cell at `pos` in world coordinates, generating its chunk if needed.
+    pub fn get(&mut self, pos: &Vec2) -> Result<Tile, ChunkError> {
+        let (chunk, local) = self.locate(pos);
+        let board = self.chunk(chunk)?;
+        Ok(board.get(&local).expect("chunks cover the world"))
+    }
+
+    /// The solved board of `chunk`, generating it if needed.
+    pub fn chunk(&mut self, chunk: Vec2) -> Result<&Board, ChunkError> {
+        if !self.chunks.contains_key(&chunk) {
+            let board = self.generate(chunk)?;
+            self.chunks.insert(chunk, board);
+        }
+        Ok(&self.chunks[&chunk])
+    }
+
+    /// Drops a generated chunk, e.g. once the player has moved away. Asking for it again
+    /// generates the same chunk.
+    pub fn forget(&mut self, chunk: Vec2) {
+        self.chunks.remove(&chunk);
+    }
+
+    /// Manhattan distance from `pos` to the nearest cell of `chunk`.
+    fn distance(&self, chunk: Vec2, pos: Vec2) -> isize {
+        let [origin_x, origin_y] = self.origin(chunk);
+        let [x, y] = pos;
+        let dx = (origin_x - x).max(x - (origin_x + self.width - 1)).max(0);
+        let dy = (origin_y - y).max(y - (origin_y + self.height - 1)).max(0);
+        dx + dy
+    }
+
+    /// Whether the cell at `pos` is solved along with `chunk`: it is part of the chunk, or of
+    /// its margin. The margin takes the cells of later neighbors within `MARGIN` of the chunk
+    /// that are closer to it than to any other chunk by at least two, so that a margin never
+    /// touches another margin, nor any chunk but the two it lies between.
+    fn solves(&self, chunk: Vec2, pos: Vec2) -> bool {
+        let (owner, _) = self.locate(&pos);
+        if owner == chunk {
+            return true;
+        }
+        let distance = self.distance(chunk, pos);
+        if self.background.is_none()
+            || distance > MARGIN
+            || World::round(owner) <= World::round(chunk)
+        {
+            return false;
+        }
+        let [x, y] = pos;
+        let reach = distance + 1;
+        (-reach..=reach).all(|dy| {
+            let span = reach - dy.abs();
+            (-span..=span).all(|dx| {
+                let (other, _) = self.locate(&[x + dx, y + dy]);
+                other == chunk || other == owner
+            })
+        })
+    }
+
+    fn generate(&mut self, chunk: Vec2) -> Result<Board, ChunkError> {
+        let directions = Grid::Square.directions();
+        let round = World::round(chunk);
+        let [origin_x, origin_y] = self.origin(chunk);
+
+        // The chunk is solved in a box reaching past it by the margin, with the cells that
+        // aren't solved along with it masked out.
+        let margin = if self.background.is_some() { MARGIN } else { 0 };
+        let [left, top] = [origin_x - margin, origin_y - margin];
+        let [width, height] = [self.width + 2 * margin, self.height + 2 * margin];
+        let mask = Mask::from_fn(width, height, |x, y| {
+            self.solves(chunk, [left + x, top + y])
+        });
+
+        let mut seeds = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                if !mask.contains(x, y) {
+                    continue;
+                }
+
+                // Next to an earlier chunk, the cell may only hold tiles compatible with its
+                // neighbor across the seam, and next to the rest of a later one, tiles that
+                // leave room for background.
+                let mut allowed = BitSet::full(self.rules.len());
+                let mut restricted = false;
+                for &direction in directions {
+                    let [dx, dy, _] = direction.offset();
+                    let pos = [left + x + dx, top + y + dy];
+                    if self.solves(chunk, pos) {
+                        continue;
+                    }
+                    let (neighbor_chunk, neighbor) = self.locate(&pos);
+                    let fits = if World::round(neighbor_chunk) < round {
+                        let Some(Tile::Collapsed(name)) =
+                            self.chunk(neighbor_chunk)?.get(&neighbor)
+                        else {
+                            unreachable!("chunks are fully collapsed");
+                        };
+                        let id = self.rules.id(&name).expect("chunks share a tileset");
+                        self.rules.compatible(direction.opposite(), id).clone()
+                    } else if let Some(background) = self.background {
+                        let mut fits = BitSet::new(self.rules.len());
+                        for tile in 0..self.rules.len() {
+                            if self.rules.compatible(direction, tile).contains(background) {
+                                fits.insert(tile);
+                            }
+                        }
+                        fits
+                    } else {
+                        continue;
+                    };
+                    for tile in 0..self.rules.len() {
+                        if !fits.contains(tile) {
+                            allowed.remove(tile);
+                        }
+                    }
+                    restricted = true;
+                }
+                if restricted {
+                    let domain = allowed.iter().map(|tile| self.rules.name(tile).to_string());
+                    seeds.push(([x, y, 0], Tile::Uncollapsed(domain.collect())));
+                }
+            }
+        }
+
+        // Borders that leave no way to fill the chunk can take the search very long to rule
+        // out, so each attempt is cut short, and given up on once a few have failed.
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| mask.contains(x, y))
+            .count();
+        let mut options = CollapseOptions {
+            seed: self.chunk_seed(chunk),
+            heuristic: self.heuristic,
+            animate: false,
+            max_backtracks: Some(BACKTRACKS_PER_CELL * cells),
+        };
+        for _ in 0..ATTEMPTS {
+            let lattice = Lattice::new(Grid::Square, width, height, 1).with_mask(&mask);
+            let mut board = Board::from_rules(self.rules.clone(), lattice);
+            board
+                .seed(&seeds)
+                .map_err(|_| ChunkError::Unsolvable { chunk })?;
+            if !board.collapse(&options) {
+                options.seed = splitmix64(options.seed);
+                continue;
+            }
+
+            // Only the chunk is kept, with its margin dropped.
+            let tiles: Vec<_> = (0..self.height)
+                .flat_map(|y| (0..self.width).map(move |x| [x, y, 0]))
+                .map(|[x, y, z]| {
+                    let tile = board
+                        .get(&[x + margin, y + margin])
+                        .expect("chunks are solved");
+                    ([x, y, z], tile)
+                })
+                .collect();
+            let lattice = Lattice::new(Grid::Square, self.width, self.height, 1);
+            let mut solved = Board::from_rules(self.rules.clone(), lattice);
+            solved
+                .seed(&tiles)
+                .expect("the chunk was solved with these tiles");
+            return Ok(solved);
+        }
+        Err(ChunkError::Unsolvable { chunk })
+    }
+}
+
+/// One step of the SplitMix64 generator, used as a 64-bit mixing function.
+fn splitmix64(state: u64) -> u64 {
+    let mut z = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
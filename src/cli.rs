@@ -0,0 +1,306 @@
+use std::{fmt::Display, path::PathBuf, str::FromStr};
+
+use wfc_tiles::Heuristic;
+
+pub const USAGE: &str = "\
+usage: wfc_tiles <command> [options]
+
+commands:
+  generate    collapse a board and write it out (the default command)
+      --tileset PATH      prototypes JSON (default: prototypes.json)
+      --sample PATH       learn the tileset from an ASCII sample instead (overlapping model)
+      --pattern-size N    size of the square patterns taken from the sample (default: 3)
+      --bounded-sample    don't wrap patterns around the edges of the sample
+      --width N           board width (default: the mask's, or 50)
+      --height N          board height (default: the mask's, or 50)
+      --depth N           number of layers, for tilesets with up and down rules (default: 1)
+      --mask PATH         ASCII board shape; spaces and '.' are not part of the board
+      --seed N            seed for every random decision (default: random)
+      --wrap-x            wrap the board around horizontally
+      --wrap-y            wrap the board around vertically
+      --wrap              wrap around on both axes
+      --partial PATH      JSON list of cells to fix or restrict before solving
+      --heuristic NAME    shannon, min-domain, scanline or random (default: shannon)
+      --animate           redraw the board after every step
+      --max-backtracks N  give up after undoing N candidates (default: 100 per cell)
+      --format FORMAT     text, svg or voxels (default: text)
+      --out PATH          write the board to PATH instead of stdout
+  validate    check a tileset for dangling references, missing directions and asymmetry
+      --tileset PATH      prototypes JSON (default: prototypes.json)
+      --symmetrize        add the missing mirrored neighbor entries to the file
+  stats       summarize a tileset
+      --tileset PATH      prototypes JSON (default: prototypes.json)
+  infer       write a tileset allowing exactly the adjacencies drawn in example maps
+      EXAMPLE...          ASCII maps drawn with the tileset's characters
+      --tileset PATH      prototypes JSON naming the characters (default: prototypes.json)
+      --out PATH          write the inferred JSON to PATH instead of stdout
+  world       generate a rectangle of chunks of an endless world, with matching seams
+      --tileset PATH      prototypes JSON (default: prototypes.json)
+      --chunk-size N      width and height of each chunk (default: 16)
+      --chunk-x N         left edge of the area, in chunk widths (default: 0)
+      --chunk-y N         top edge of the area, in chunk heights (default: 0)
+      --columns N         width of the area, in chunk widths (default: 3)
+      --rows N            height of the area, in chunk heights (default: 3)
+      --seed N            world seed; each chunk's seed is mixed from it (default: random)
+      --heuristic NAME    shannon, min-domain, scanline or random (default: shannon)
+      --out PATH          write the world to PATH instead of stdout
+  help        show this message";
+
+const DEFAULT_TILESET: &str = "prototypes.json";
+pub const DEFAULT_PATTERN_SIZE: usize = 3;
+pub const DEFAULT_SIZE: isize = 50;
+pub const DEFAULT_BACKTRACKS_PER_CELL: usize = 100;
+const DEFAULT_CHUNK_SIZE: isize = 16;
+const DEFAULT_CHUNKS: isize = 3;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Text,
+    Svg,
+    /// One `x y z tile` line per cell; see `Board::render_voxels`.
+    Voxels,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "svg" => Ok(Format::Svg),
+            "voxels" => Ok(Format::Voxels),
+            _ => Err(format!(
+                "unknown format \"{}\" (expected text, svg or voxels)",
+                s
+            )),
+        }
+    }
+}
+
+pub enum Command {
+    Generate(GenerateArgs),
+    Validate(ValidateArgs),
+    Stats(StatsArgs),
+    Infer(InferArgs),
+    World(WorldArgs),
+    Help,
+}
+
+pub struct GenerateArgs {
+    pub tileset: PathBuf,
+    pub sample: Option<PathBuf>,
+    pub pattern_size: usize,
+    pub bounded_sample: bool,
+    pub width: Option<isize>,
+    pub height: Option<isize>,
+    pub depth: isize,
+    pub mask: Option<PathBuf>,
+    pub wrap_x: bool,
+    pub wrap_y: bool,
+    pub partial: Option<PathBuf>,
+    pub seed: Option<u64>,
+    pub heuristic: Heuristic,
+    pub animate: bool,
+    pub max_backtracks: Option<usize>,
+    pub format: Format,
+    pub out: Option<PathBuf>,
+}
+
+pub struct ValidateArgs {
+    pub tileset: PathBuf,
+    pub symmetrize: bool,
+}
+
+pub struct StatsArgs {
+    pub tileset: PathBuf,
+}
+
+pub struct InferArgs {
+    pub tileset: PathBuf,
+    pub examples: Vec<PathBuf>,
+    pub out: Option<PathBuf>,
+}
+
+pub struct WorldArgs {
+    pub tileset: PathBuf,
+    pub chunk_size: isize,
+    pub chunk: [isize; 2],
+    pub columns: isize,
+    pub rows: isize,
+    pub seed: Option<u64>,
+    pub heuristic: Heuristic,
+    pub out: Option<PathBuf>,
+}
+
+impl Default for GenerateArgs {
+    fn default() -> Self {
+        GenerateArgs {
+            tileset: DEFAULT_TILESET.into(),
+            sample: None,
+            pattern_size: DEFAULT_PATTERN_SIZE,
+            bounded_sample: false,
+            width: None,
+            height: None,
+            depth: 1,
+            mask: None,
+            wrap_x: false,
+            wrap_y: false,
+            partial: None,
+            seed: None,
+            heuristic: Heuristic::default(),
+            animate: false,
+            max_backtracks: None,
+            format: Format::default(),
+            out: None,
+        }
+    }
+}
+
+pub fn parse(args: &[String]) -> Result<Command, String> {
+    let (command, rest) = match args.first().map(String::as_str) {
+        None => ("generate", args),
+        Some(arg) if arg.starts_with("--") && arg != "--help" => ("generate", args),
+        Some(command) => (command, &args[1..]),
+    };
+    let mut flags = rest.iter();
+
+    match command {
+        "generate" => {
+            let mut generate = GenerateArgs::default();
+            while let Some(flag) = flags.next() {
+                match flag.as_str() {
+                    "--tileset" => generate.tileset = value(flag, &mut flags)?.into(),
+                    "--sample" => generate.sample = Some(value(flag, &mut flags)?.into()),
+                    "--pattern-size" => {
+                        generate.pattern_size = positive(flag, &mut flags)? as usize
+                    }
+                    "--bounded-sample" => generate.bounded_sample = true,
+                    "--width" => generate.width = Some(positive(flag, &mut flags)?),
+                    "--height" => generate.height = Some(positive(flag, &mut flags)?),
+                    "--depth" => generate.depth = positive(flag, &mut flags)?,
+                    "--mask" => generate.mask = Some(value(flag, &mut flags)?.into()),
+                    "--wrap-x" => generate.wrap_x = true,
+                    "--wrap-y" => generate.wrap_y = true,
+                    "--wrap" => (generate.wrap_x, generate.wrap_y) = (true, true),
+                    "--partial" => generate.partial = Some(value(flag, &mut flags)?.into()),
+                    "--seed" => generate.seed = Some(parse_value(flag, &mut flags)?),
+                    "--heuristic" => generate.heuristic = parse_value(flag, &mut flags)?,
+                    "--animate" => generate.animate = true,
+                    "--max-backtracks" => {
+                        generate.max_backtracks = Some(positive(flag, &mut flags)? as usize)
+                    }
+                    "--format" => generate.format = parse_value(flag, &mut flags)?,
+                    "--out" => generate.out = Some(value(flag, &mut flags)?.into()),
+                    _ => return Err(unknown_flag(command, flag)),
+                }
+            }
+            Ok(Command::Generate(generate))
+        }
+        "validate" => {
+            let mut validate = ValidateArgs {
+                tileset: DEFAULT_TILESET.into(),
+                symmetrize: false,
+            };
+            while let Some(flag) = flags.next() {
+                match flag.as_str() {
+                    "--tileset" => validate.tileset = value(flag, &mut flags)?.into(),
+                    "--symmetrize" => validate.symmetrize = true,
+                    _ => return Err(unknown_flag(command, flag)),
+                }
+            }
+            Ok(Command::Validate(validate))
+        }
+        "stats" => {
+            let mut stats = StatsArgs {
+                tileset: DEFAULT_TILESET.into(),
+            };
+            while let Some(flag) = flags.next() {
+                match flag.as_str() {
+                    "--tileset" => stats.tileset = value(flag, &mut flags)?.into(),
+                    _ => return Err(unknown_flag(command, flag)),
+                }
+            }
+            Ok(Command::Stats(stats))
+        }
+        "infer" => {
+            let mut infer = InferArgs {
+                tileset: DEFAULT_TILESET.into(),
+                examples: Vec::new(),
+                out: None,
+            };
+            while let Some(flag) = flags.next() {
+                match flag.as_str() {
+                    "--tileset" => infer.tileset = value(flag, &mut flags)?.into(),
+                    "--out" => infer.out = Some(value(flag, &mut flags)?.into()),
+                    _ if flag.starts_with("--") => return Err(unknown_flag(command, flag)),
+                    _ => infer.examples.push(flag.into()),
+                }
+            }
+            if infer.examples.is_empty() {
+                return Err(format!(
+                    "infer expects at least one example map\n\n{}",
+                    USAGE
+                ));
+            }
+            Ok(Command::Infer(infer))
+        }
+        "world" => {
+            let mut world = WorldArgs {
+                tileset: DEFAULT_TILESET.into(),
+                chunk_size: DEFAULT_CHUNK_SIZE,
+                chunk: [0, 0],
+                columns: DEFAULT_CHUNKS,
+                rows: DEFAULT_CHUNKS,
+                seed: None,
+                heuristic: Heuristic::default(),
+                out: None,
+            };
+            while let Some(flag) = flags.next() {
+                match flag.as_str() {
+                    "--tileset" => world.tileset = value(flag, &mut flags)?.into(),
+                    "--chunk-size" => world.chunk_size = positive(flag, &mut flags)?,
+                    "--chunk-x" => world.chunk[0] = parse_value(flag, &mut flags)?,
+                    "--chunk-y" => world.chunk[1] = parse_value(flag, &mut flags)?,
+                    "--columns" => world.columns = positive(flag, &mut flags)?,
+                    "--rows" => world.rows = positive(flag, &mut flags)?,
+                    "--seed" => world.seed = Some(parse_value(flag, &mut flags)?),
+                    "--heuristic" => world.heuristic = parse_value(flag, &mut flags)?,
+                    "--out" => world.out = Some(value(flag, &mut flags)?.into()),
+                    _ => return Err(unknown_flag(command, flag)),
+                }
+            }
+            Ok(Command::World(world))
+        }
+        "help" | "--help" | "-h" => Ok(Command::Help),
+        _ => Err(format!("unknown command \"{}\"\n\n{}", command, USAGE)),
+    }
+}
+
+fn value<'a>(flag: &str, flags: &mut impl Iterator<Item = &'a String>) -> Result<&'a str, String> {
+    flags
+        .next()
+        .map(String::as_str)
+        .ok_or_else(|| format!("{} expects a value", flag))
+}
+
+fn parse_value<'a, T>(flag: &str, flags: &mut impl Iterator<Item = &'a String>) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let raw = value(flag, flags)?;
+    raw.parse()
+        .map_err(|err| format!("{}: invalid value \"{}\": {}", flag, raw, err))
+}
+
+fn positive<'a>(flag: &str, flags: &mut impl Iterator<Item = &'a String>) -> Result<isize, String> {
+    match parse_value(flag, flags)? {
+        n if n > 0 => Ok(n),
+        _ => Err(format!("{} must be at least 1", flag)),
+    }
+}
+
+fn unknown_flag(command: &str, flag: &str) -> String {
+    format!("unknown option \"{}\" for {}\n\n{}", flag, command, USAGE)
+}
@@ -0,0 +1,169 @@
+use std::{collections::BTreeSet, fmt, str::FromStr, sync::Mutex};
+
+use crate::board::Vec3;
+
+/// A side of a cell, as named in `valid_neighbors` and `sockets`. Which directions a board
+/// uses depends on its `Grid`.
+///
+/// Square boards use screen coordinates: `[x, y]` with x counting columns from the left and
+/// y counting rows from the top, so `Above` is one row up the rendered output and `Right` is
+/// the next character on the same line. The solver, renderer, masks, partial boards and
+/// example maps all share this convention.
+///
+/// Hex boards use axial coordinates `[q, r]`, with r counting rows from the top as well.
+/// Their rows are horizontal, so a hex has neighbors to its `Right` and `Left` and two on
+/// each of the rows above and below.
+///
+/// 3D boards stack square layers along a z axis counting layers from the bottom, so `Up` is
+/// the same `[x, y]` one layer higher; see `Vec3`.
+///
+/// `Adjacent` labels the links of a `Graph` whose cells have no sides, like rooms joined by
+/// doors. It is its own opposite. Graphs whose links do have sides name them with a `Label`
+/// of their own, like the `north` and `south` doors of a room, and pair them up as opposites
+/// themselves; see `Graph::pair`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    Right,
+    Left,
+    Above,
+    Below,
+    AboveRight,
+    AboveLeft,
+    BelowRight,
+    BelowLeft,
+    Up,
+    Down,
+    Adjacent,
+    /// Any other name, for the links of a `Graph`.
+    Label(&'static str),
+}
+
+/// Every label read from a tileset, so that each name is leaked only once.
+static LABELS: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+
+impl Direction {
+    /// Every direction but labels, in the order used to index per-direction tables.
+    pub const ALL: [Direction; 11] = [
+        Direction::Right,
+        Direction::Left,
+        Direction::Above,
+        Direction::Below,
+        Direction::AboveRight,
+        Direction::AboveLeft,
+        Direction::BelowRight,
+        Direction::BelowLeft,
+        Direction::Up,
+        Direction::Down,
+        Direction::Adjacent,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Direction::Right => "right",
+            Direction::Left => "left",
+            Direction::Above => "above",
+            Direction::Below => "below",
+            Direction::AboveRight => "above_right",
+            Direction::AboveLeft => "above_left",
+            Direction::BelowRight => "below_right",
+            Direction::BelowLeft => "below_left",
+            Direction::Up => "up",
+            Direction::Down => "down",
+            Direction::Adjacent => "adjacent",
+            Direction::Label(label) => label,
+        }
+    }
+
+    /// The direction called `name`, which is a `Label` unless it names one of `ALL`.
+    pub fn label(name: &str) -> Direction {
+        if let Ok(direction) = name.parse() {
+            return direction;
+        }
+        let mut labels = LABELS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let label = match labels.get(name) {
+            Some(&label) => label,
+            None => {
+                let label: &'static str = Box::leak(name.into());
+                labels.insert(label);
+                label
+            }
+        };
+        Direction::Label(label)
+    }
+
+    /// Position of this direction in `ALL`.
+    ///
+    /// Panics on a `Label`, which isn't in it.
+    pub fn index(self) -> usize {
+        match self {
+            Direction::Right => 0,
+            Direction::Left => 1,
+            Direction::Above => 2,
+            Direction::Below => 3,
+            Direction::AboveRight => 4,
+            Direction::AboveLeft => 5,
+            Direction::BelowRight => 6,
+            Direction::BelowLeft => 7,
+            Direction::Up => 8,
+            Direction::Down => 9,
+            Direction::Adjacent => 10,
+            Direction::Label(label) => panic!("label \"{}\" has no index", label),
+        }
+    }
+
+    /// The opposite side on the square, hex and 3D grids. A label is its own opposite unless
+    /// a `Graph` pairs it with another.
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Right => Direction::Left,
+            Direction::Left => Direction::Right,
+            Direction::Above => Direction::Below,
+            Direction::Below => Direction::Above,
+            Direction::AboveRight => Direction::BelowLeft,
+            Direction::AboveLeft => Direction::BelowRight,
+            Direction::BelowRight => Direction::AboveLeft,
+            Direction::BelowLeft => Direction::AboveRight,
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Adjacent | Direction::Label(_) => self,
+        }
+    }
+
+    /// The step from a cell to its neighbor in this direction: in screen coordinates for the
+    /// square directions and in axial coordinates for the hex ones, with the change of layer
+    /// last. Graph cells have no positions, so `Adjacent` and labels don't move at all.
+    pub fn offset(self) -> Vec3 {
+        match self {
+            Direction::Right => [1, 0, 0],
+            Direction::Left => [-1, 0, 0],
+            Direction::Above => [0, -1, 0],
+            Direction::Below => [0, 1, 0],
+            Direction::AboveRight => [1, -1, 0],
+            Direction::AboveLeft => [0, -1, 0],
+            Direction::BelowRight => [0, 1, 0],
+            Direction::BelowLeft => [-1, 1, 0],
+            Direction::Up => [0, 0, 1],
+            Direction::Down => [0, 0, -1],
+            Direction::Adjacent | Direction::Label(_) => [0, 0, 0],
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Direction::ALL
+            .into_iter()
+            .find(|direction| direction.name() == s)
+            .ok_or_else(|| format!("unknown direction \"{}\"", s))
+    }
+}
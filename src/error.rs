@@ -0,0 +1,219 @@
+use std::{fmt, path::PathBuf};
+
+use crate::{
+    board::{Vec2, Vec3},
+    grid::Grid,
+};
+
+/// Everything that can go wrong while loading a tileset from prototypes JSON.
+///
+/// `path` is the JSON path of the offending value, e.g. `$.h.valid_neighbors.left[2]`.
+#[derive(Debug)]
+pub enum TilesetError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Parse(json::Error),
+    MissingField {
+        tile: String,
+        path: String,
+    },
+    WrongType {
+        tile: String,
+        path: String,
+        expected: &'static str,
+    },
+    UnknownTile {
+        tile: String,
+        path: String,
+        reference: String,
+    },
+    UnknownDirection {
+        tile: String,
+        path: String,
+        direction: String,
+    },
+    /// A tile is defined under a name reserved for the board edge or for a generated variant
+    /// of a symmetric tile.
+    ReservedName {
+        tile: String,
+        path: String,
+    },
+}
+
+impl fmt::Display for TilesetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TilesetError::Io { path, source } => {
+                write!(f, "could not read {}: {}", path.display(), source)
+            }
+            TilesetError::Parse(source) => write!(f, "invalid JSON: {}", source),
+            TilesetError::MissingField { path, .. } => write!(f, "{}: missing field", path),
+            TilesetError::WrongType { path, expected, .. } => {
+                write!(f, "{}: expected {}", path, expected)
+            }
+            TilesetError::UnknownTile {
+                path, reference, ..
+            } => write!(f, "{}: unknown tile \"{}\"", path, reference),
+            TilesetError::UnknownDirection {
+                path, direction, ..
+            } => write!(f, "{}: unknown direction \"{}\"", path, direction),
+            TilesetError::ReservedName { path, tile } => {
+                write!(f, "{}: \"{}\" is a reserved tile name", path, tile)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TilesetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TilesetError::Io { source, .. } => Some(source),
+            TilesetError::Parse(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<json::Error> for TilesetError {
+    fn from(source: json::Error) -> Self {
+        TilesetError::Parse(source)
+    }
+}
+
+/// Everything that can go wrong while loading a partial board or applying it to a `Board`.
+///
+/// `path` is the JSON path of the offending value, e.g. `$[3].pos[0]`, and positions are
+/// board coordinates.
+#[derive(Debug)]
+pub enum SeedError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Parse(json::Error),
+    MissingField {
+        path: String,
+    },
+    WrongType {
+        path: String,
+        expected: &'static str,
+    },
+    OutOfBounds {
+        pos: Vec3,
+    },
+    UnknownTile {
+        pos: Vec3,
+        tile: String,
+    },
+    /// The seed at `pos` allows no tile at all.
+    EmptyDomain {
+        pos: Vec3,
+    },
+    /// Applying the seed at `pos` left `cell` with no possible tile.
+    Contradiction {
+        pos: Vec3,
+        cell: Vec3,
+    },
+    /// The tileset's edge rules alone leave `cell` with no possible tile.
+    EdgeContradiction {
+        cell: Vec3,
+    },
+}
+
+impl fmt::Display for SeedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeedError::Io { path, source } => {
+                write!(f, "could not read {}: {}", path.display(), source)
+            }
+            SeedError::Parse(source) => write!(f, "invalid JSON: {}", source),
+            SeedError::MissingField { path } => write!(f, "{}: missing field", path),
+            SeedError::WrongType { path, expected } => {
+                write!(f, "{}: expected {}", path, expected)
+            }
+            SeedError::OutOfBounds { pos } => {
+                write!(f, "seed at {} is outside the board", Position(pos))
+            }
+            SeedError::UnknownTile { pos, tile } => {
+                write!(f, "seed at {}: unknown tile \"{}\"", Position(pos), tile)
+            }
+            SeedError::EmptyDomain { pos } => {
+                write!(f, "seed at {} allows no tiles", Position(pos))
+            }
+            SeedError::Contradiction { pos, cell } => write!(
+                f,
+                "seed at {} contradicts earlier seeds: no tile fits at {}",
+                Position(pos),
+                Position(cell)
+            ),
+            SeedError::EdgeContradiction { cell } => write!(
+                f,
+                "edge rules leave no tile that fits at {}",
+                Position(cell)
+            ),
+        }
+    }
+}
+
+/// Displays a position as `(x, y)`, adding the layer only when it isn't the bottom one.
+struct Position<'a>(&'a Vec3);
+
+impl fmt::Display for Position<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            [x, y, 0] => write!(f, "({}, {})", x, y),
+            [x, y, z] => write!(f, "({}, {}, {})", x, y, z),
+        }
+    }
+}
+
+impl std::error::Error for SeedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SeedError::Io { source, .. } => Some(source),
+            SeedError::Parse(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<json::Error> for SeedError {
+    fn from(source: json::Error) -> Self {
+        SeedError::Parse(source)
+    }
+}
+
+/// Everything that can go wrong while generating a chunk of a `World`.
+#[derive(Debug)]
+pub enum ChunkError {
+    /// Worlds are only chunked on square grids.
+    UnsupportedGrid(Grid),
+    /// Chunks have to be at least one cell wide and high.
+    InvalidSize { width: isize, height: isize },
+    /// No tiling of `chunk` that fits the borders of its neighbors was found in time.
+    Unsolvable { chunk: Vec2 },
+}
+
+impl fmt::Display for ChunkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChunkError::UnsupportedGrid(grid) => {
+                write!(f, "only square tilesets can be chunked, not {:?}", grid)
+            }
+            ChunkError::InvalidSize { width, height } => {
+                write!(
+                    f,
+                    "chunks must be at least 1 by 1 cells, not {} by {}",
+                    width, height
+                )
+            }
+            ChunkError::Unsolvable { chunk: [cx, cy] } => {
+                write!(f, "no solution found for chunk ({}, {})", cx, cy)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ChunkError {}
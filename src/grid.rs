@@ -0,0 +1,257 @@
+use crate::{
+    board::{Vec2, Vec3},
+    direction::Direction,
+    mask::Mask,
+    topology::Topology,
+};
+
+/// The shape of a board's cells, which decides their neighbors and how positions are laid
+/// out. A tileset's grid follows from the directions it uses; see `WFCAdjacencyMap::grid`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Grid {
+    /// Square cells with four neighbors.
+    #[default]
+    Square,
+    /// Pointy-topped hexagons with six neighbors, in rows.
+    Hex,
+    /// Square layers stacked on top of each other: cubes with six neighbors.
+    Cube,
+    /// Cells linked in no particular layout, as in a `Graph` whose links are
+    /// `Direction::Adjacent` or labelled. There is no `Lattice` of them.
+    Graph,
+}
+
+impl Grid {
+    pub fn directions(&self) -> &'static [Direction] {
+        match self {
+            Grid::Square => &[
+                Direction::Right,
+                Direction::Left,
+                Direction::Above,
+                Direction::Below,
+            ],
+            Grid::Hex => &[
+                Direction::Right,
+                Direction::Left,
+                Direction::AboveRight,
+                Direction::AboveLeft,
+                Direction::BelowRight,
+                Direction::BelowLeft,
+            ],
+            Grid::Cube => &[
+                Direction::Right,
+                Direction::Left,
+                Direction::Above,
+                Direction::Below,
+                Direction::Up,
+                Direction::Down,
+            ],
+            Grid::Graph => &[Direction::Adjacent],
+        }
+    }
+
+    /// Whether cells on this grid can have neighbors in `direction`: one of `directions`, or
+    /// any label on a graph.
+    pub fn has(&self, direction: Direction) -> bool {
+        match direction {
+            Direction::Label(_) => *self == Grid::Graph,
+            _ => self.directions().contains(&direction),
+        }
+    }
+
+    /// The `[column, row]` a position is stored and drawn at, within its layer. Square and
+    /// cube positions are stored as they are; hex boards store their axial positions in
+    /// "odd-r" layout, with odd rows shifted half a cell to the right, so that a rectangle of
+    /// storage is a rectangular map.
+    pub fn to_storage(&self, pos: Vec2) -> Vec2 {
+        match self {
+            Grid::Square | Grid::Cube | Grid::Graph => pos,
+            Grid::Hex => {
+                let [q, r] = pos;
+                [q + (r - (r & 1)) / 2, r]
+            }
+        }
+    }
+
+    /// The inverse of `to_storage`.
+    pub fn from_storage(&self, storage: Vec2) -> Vec2 {
+        match self {
+            Grid::Square | Grid::Cube | Grid::Graph => storage,
+            Grid::Hex => {
+                let [column, row] = storage;
+                [column - (row - (row & 1)) / 2, row]
+            }
+        }
+    }
+}
+
+/// A box of `Grid` cells, `depth` layers of `width` by `height`: the topology of the boards
+/// made by `Board::create`.
+///
+/// Cells are stored row by row, `width` columns at a time, in the layout of `Grid::to_storage`,
+/// and layer by layer from the bottom.
+#[derive(Clone, Debug)]
+pub struct Lattice {
+    grid: Grid,
+    width: isize,
+    height: isize,
+    depth: isize,
+    /// Whether the x and y axes wrap around, making opposite edges neighbors.
+    periodic: [bool; 2],
+    /// Whether each cell is part of the board.
+    active: Vec<bool>,
+}
+
+impl Lattice {
+    /// Panics on `Grid::Graph`, which has no layout to fill a box with.
+    pub fn new(grid: Grid, width: isize, height: isize, depth: isize) -> Self {
+        assert_ne!(grid, Grid::Graph, "graph tilesets need a Graph topology");
+        Lattice {
+            grid,
+            width,
+            height,
+            depth,
+            periodic: [false, false],
+            active: vec![true; (width * height * depth) as usize],
+        }
+    }
+
+    /// Makes the lattice wrap around along x and/or y, so that e.g. the left column
+    /// constrains the right column. Useful for seamless repeating textures and wrapping world
+    /// maps. Hex lattices need an even height to wrap vertically without a seam.
+    pub fn with_periodic(mut self, periodic_x: bool, periodic_y: bool) -> Self {
+        self.periodic = [periodic_x, periodic_y];
+        self
+    }
+
+    /// Removes the cells outside `mask` from every layer, aligning the mask's top-left
+    /// corner with the lattice's. Removed cells act like the board edge for their neighbors,
+    /// including for edge rules. Masks are drawn as the board is rendered.
+    pub fn with_mask(mut self, mask: &Mask) -> Self {
+        for cell in 0..self.active.len() {
+            let [column, row, _layer] = self.storage(cell);
+            self.active[cell] = mask.contains(column, row);
+        }
+        self
+    }
+
+    pub fn grid(&self) -> Grid {
+        self.grid
+    }
+
+    pub fn width(&self) -> isize {
+        self.width
+    }
+
+    pub fn height(&self) -> isize {
+        self.height
+    }
+
+    pub fn depth(&self) -> isize {
+        self.depth
+    }
+
+    /// The cell at `pos`, unless it is off the board or masked out.
+    pub fn cell(&self, pos: &Vec3) -> Option<usize> {
+        let [x, y, z] = *pos;
+        let [column, row] = self.grid.to_storage([x, y]);
+        self.stored_cell([column, row, z])
+    }
+
+    /// The position of `cell`.
+    pub fn pos(&self, cell: usize) -> Vec3 {
+        let [column, row, layer] = self.storage(cell);
+        let [x, y] = self.grid.from_storage([column, row]);
+        [x, y, layer]
+    }
+
+    /// The cell stored at `[column, row, layer]`, unless it is off the board or masked out.
+    pub(crate) fn stored_cell(&self, storage: Vec3) -> Option<usize> {
+        let [column, row, layer] = storage;
+        if column < 0
+            || row < 0
+            || layer < 0
+            || column >= self.width
+            || row >= self.height
+            || layer >= self.depth
+        {
+            return None;
+        }
+        let cell = ((layer * self.height + row) * self.width + column) as usize;
+        self.active[cell].then_some(cell)
+    }
+
+    pub(crate) fn storage(&self, cell: usize) -> Vec3 {
+        let cell = cell as isize;
+        [
+            cell % self.width,
+            cell / self.width % self.height,
+            cell / (self.width * self.height),
+        ]
+    }
+}
+
+impl Topology for Lattice {
+    fn len(&self) -> usize {
+        self.active.len()
+    }
+
+    fn contains(&self, cell: usize) -> bool {
+        self.active[cell]
+    }
+
+    fn directions(&self) -> &[Direction] {
+        self.grid.directions()
+    }
+
+    /// Positions past a periodic edge wrap around to the other side.
+    fn neighbors(&self, cell: usize) -> Vec<(Direction, usize)> {
+        let [x, y, z] = self.pos(cell);
+        self.grid
+            .directions()
+            .iter()
+            .filter_map(|&direction| {
+                let [dx, dy, dz] = direction.offset();
+                let [mut column, mut row] = self.grid.to_storage([x + dx, y + dy]);
+                if self.periodic[0] {
+                    column = column.rem_euclid(self.width);
+                }
+                if self.periodic[1] {
+                    row = row.rem_euclid(self.height);
+                }
+                Some((direction, self.stored_cell([column, row, z + dz])?))
+            })
+            .collect()
+    }
+
+    /// One line per row, with masked cells left blank. Hex cells are separated by spaces,
+    /// with odd rows indented by one so that each cell sits between its neighbors above and
+    /// below. Layers follow each other from the bottom up, separated by blank lines.
+    fn render(&self, characters: &[&str]) -> String {
+        let mut output = String::new();
+        for layer in 0..self.depth {
+            if layer > 0 {
+                output.push('\n');
+            }
+            for row in 0..self.height {
+                let row_characters: Vec<&str> = (0..self.width)
+                    .map(|column| match self.stored_cell([column, row, layer]) {
+                        Some(cell) => characters[cell],
+                        None => " ",
+                    })
+                    .collect();
+                match self.grid {
+                    Grid::Hex => {
+                        if row % 2 == 1 {
+                            output.push(' ');
+                        }
+                        output.push_str(row_characters.join(" ").trim_end());
+                    }
+                    _ => output.push_str(&row_characters.concat()),
+                }
+                output.push('\n');
+            }
+        }
+        output
+    }
+}
@@ -0,0 +1,153 @@
+use std::{cmp::Ordering, collections::BinaryHeap, fmt, str::FromStr};
+
+use rand::{rngs::StdRng, Rng};
+
+use crate::{bitset, board::Board, topology::Topology};
+
+/// How `collapse` picks the next cell to collapse.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Heuristic {
+    /// Fewest remaining candidates, ties broken at random.
+    MinDomain,
+    /// First uncollapsed cell in reading order.
+    Scanline,
+    /// Any uncollapsed cell, uniformly at random.
+    Random,
+    /// Lowest weighted Shannon entropy, with a little noise to break ties.
+    #[default]
+    Shannon,
+}
+
+impl Heuristic {
+    pub const ALL: [Heuristic; 4] = [
+        Heuristic::MinDomain,
+        Heuristic::Scanline,
+        Heuristic::Random,
+        Heuristic::Shannon,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Heuristic::MinDomain => "min-domain",
+            Heuristic::Scanline => "scanline",
+            Heuristic::Random => "random",
+            Heuristic::Shannon => "shannon",
+        }
+    }
+}
+
+impl fmt::Display for Heuristic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Heuristic {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Heuristic::ALL
+            .into_iter()
+            .find(|heuristic| heuristic.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Heuristic::ALL.iter().map(Heuristic::name).collect();
+                format!(
+                    "unknown heuristic \"{}\" (expected one of {})",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Scale of the random noise added to scores, small enough to only break ties.
+const TIE_NOISE: f64 = 1e-6;
+
+/// How many entries per cell the heap may grow to before its stale entries are dropped.
+const STALE_FACTOR: usize = 4;
+
+struct Candidate {
+    score: f64,
+    cell: usize,
+    version: u32,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    /// Reversed, so the `BinaryHeap` pops the lowest score first.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .score
+            .total_cmp(&self.score)
+            .then_with(|| other.cell.cmp(&self.cell))
+    }
+}
+
+/// Uncollapsed cells ordered by a `Heuristic`. Every change to a cell's domain pushes a new
+/// entry; older entries for the same cell are skipped when popped, or dropped all at once when
+/// they outnumber the cells `STALE_FACTOR` times over.
+pub(crate) struct CellQueue {
+    heuristic: Heuristic,
+    heap: BinaryHeap<Candidate>,
+    versions: Vec<u32>,
+}
+
+impl CellQueue {
+    pub fn new(heuristic: Heuristic, cells: usize) -> Self {
+        CellQueue {
+            heuristic,
+            heap: BinaryHeap::new(),
+            versions: vec![0; cells],
+        }
+    }
+
+    pub fn push<T: Topology>(&mut self, board: &Board<T>, cell: usize, rng: &mut StdRng) {
+        let domain = board.domain(cell);
+        let score = match self.heuristic {
+            Heuristic::MinDomain => bitset::count(domain) as f64 + rng.gen::<f64>() * TIE_NOISE,
+            // Lattices number their cells in reading order, layer by layer.
+            Heuristic::Scanline => cell as f64,
+            Heuristic::Random => rng.gen(),
+            Heuristic::Shannon => board.rules().entropy(domain) + rng.gen::<f64>() * TIE_NOISE,
+        };
+        self.versions[cell] = self.versions[cell].wrapping_add(1);
+        self.heap.push(Candidate {
+            score,
+            cell,
+            version: self.versions[cell],
+        });
+
+        if self.heap.len() > STALE_FACTOR * self.versions.len() {
+            let versions = &self.versions;
+            self.heap.retain(|candidate| {
+                !board.is_cell_collapsed(candidate.cell)
+                    && versions[candidate.cell] == candidate.version
+            });
+        }
+    }
+
+    /// The best uncollapsed cell, or `None` once every cell is collapsed.
+    pub fn pop<T: Topology>(&mut self, board: &Board<T>) -> Option<usize> {
+        while let Some(candidate) = self.heap.pop() {
+            if !board.is_cell_collapsed(candidate.cell)
+                && self.versions[candidate.cell] == candidate.version
+            {
+                return Some(candidate.cell);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,168 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use json::JsonValue;
+
+use crate::{adjacency::AdjacencyMap, direction::Direction, grid::Grid};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum InferError {
+    /// No tile in the tileset is drawn with `character`. `row` and `column` count from 1.
+    UnknownCharacter {
+        row: usize,
+        column: usize,
+        character: char,
+    },
+    /// Several tiles are drawn with `character`, so the example doesn't say which one is
+    /// meant.
+    AmbiguousCharacter {
+        row: usize,
+        column: usize,
+        character: char,
+        tiles: Vec<String>,
+    },
+}
+
+impl fmt::Display for InferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InferError::UnknownCharacter {
+                row,
+                column,
+                character,
+            } => write!(
+                f,
+                "{}:{}: no tile is drawn as \"{}\"",
+                row, column, character
+            ),
+            InferError::AmbiguousCharacter {
+                row,
+                column,
+                character,
+                tiles,
+            } => write!(
+                f,
+                "{}:{}: \"{}\" could be any of {}",
+                row,
+                column,
+                character,
+                tiles.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InferError {}
+
+/// Collects the adjacencies seen in hand-drawn example maps, to write out a tileset that
+/// allows exactly those. Characters are mapped back to tile names through an existing
+/// tileset.
+pub struct Inference {
+    tiles_by_character: HashMap<String, Vec<String>>,
+    characters: HashMap<String, String>,
+    /// Occurrences of each tile, which become its weight.
+    counts: BTreeMap<String, usize>,
+    /// `neighbors[tile][direction.index()]` holds the tiles seen in that direction from `tile`.
+    neighbors: BTreeMap<String, [Vec<String>; 4]>,
+}
+
+impl Inference {
+    pub fn new(prototype_map: &AdjacencyMap) -> Self {
+        let mut tiles_by_character: HashMap<String, Vec<String>> = HashMap::new();
+        let mut characters = HashMap::new();
+        for (tile_name, tile) in prototype_map.iter() {
+            characters.insert(tile_name.clone(), tile.character.clone());
+            tiles_by_character
+                .entry(tile.character.clone())
+                .or_default()
+                .push(tile_name.clone());
+        }
+        for tiles in tiles_by_character.values_mut() {
+            tiles.sort();
+        }
+        Inference {
+            tiles_by_character,
+            characters,
+            counts: BTreeMap::new(),
+            neighbors: BTreeMap::new(),
+        }
+    }
+
+    /// Adds the tiles and adjacencies drawn in one example, top row first. Nothing is added
+    /// if the example contains a character that doesn't name exactly one tile.
+    pub fn add(&mut self, example: &str) -> Result<(), InferError> {
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        for (row, line) in example.lines().enumerate() {
+            let mut tiles = Vec::new();
+            for (column, character) in line.chars().enumerate() {
+                tiles.push(self.tile(character, row + 1, column + 1)?);
+            }
+            rows.push(tiles);
+        }
+
+        let mut observed = Vec::new();
+        for (row, tiles) in rows.iter().enumerate() {
+            for (column, tile) in tiles.iter().enumerate() {
+                *self.counts.entry(tile.clone()).or_insert(0) += 1;
+                if let Some(right) = tiles.get(column + 1) {
+                    observed.push((tile, Direction::Right, right));
+                }
+                if let Some(above) = row.checked_sub(1).and_then(|row| rows[row].get(column)) {
+                    observed.push((tile, Direction::Above, above));
+                }
+            }
+        }
+
+        for (tile, direction, neighbor) in observed {
+            self.observe(tile, direction, neighbor);
+            self.observe(neighbor, direction.opposite(), tile);
+        }
+        Ok(())
+    }
+
+    fn tile(&self, character: char, row: usize, column: usize) -> Result<String, InferError> {
+        match self.tiles_by_character.get(character.to_string().as_str()) {
+            None => Err(InferError::UnknownCharacter {
+                row,
+                column,
+                character,
+            }),
+            Some(tiles) if tiles.len() > 1 => Err(InferError::AmbiguousCharacter {
+                row,
+                column,
+                character,
+                tiles: tiles.clone(),
+            }),
+            Some(tiles) => Ok(tiles[0].clone()),
+        }
+    }
+
+    fn observe(&mut self, tile: &str, direction: Direction, neighbor: &str) {
+        let list = &mut self.neighbors.entry(tile.to_string()).or_default()[direction.index()];
+        if let Err(position) = list.binary_search_by(|probe| probe.as_str().cmp(neighbor)) {
+            list.insert(position, neighbor.to_string());
+        }
+    }
+
+    /// The inferred tileset as prototypes JSON, with every tile seen so far weighted by its
+    /// number of occurrences.
+    pub fn to_json(&self) -> JsonValue {
+        let mut prototypes_json = JsonValue::new_object();
+        for (tile_name, &count) in self.counts.iter() {
+            let mut valid_neighbors = JsonValue::new_object();
+            let lists = self.neighbors.get(tile_name).cloned().unwrap_or_default();
+            for (direction, list) in Grid::Square.directions().iter().zip(lists) {
+                valid_neighbors[direction.name()] = list.into();
+            }
+
+            let mut tile_description = JsonValue::new_object();
+            tile_description["char"] = self.characters[tile_name].as_str().into();
+            tile_description["valid_neighbors"] = valid_neighbors;
+            tile_description["weight"] = count.into();
+            prototypes_json[tile_name.as_str()] = tile_description;
+        }
+        prototypes_json
+    }
+}
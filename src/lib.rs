@@ -0,0 +1,30 @@
+pub mod adjacency;
+pub mod bitset;
+pub mod board;
+pub mod chunk;
+pub mod direction;
+pub mod error;
+pub mod grid;
+pub mod heuristic;
+pub mod infer;
+pub mod mask;
+pub mod overlap;
+pub mod rules;
+pub mod seed;
+pub mod symmetry;
+pub mod topology;
+pub mod validate;
+
+pub use adjacency::{AdjacencyMap, BoardCharacter, Socket, WFCAdjacencyMap};
+pub use board::{Board, CollapseOptions, Domain, Tile, Vec2, Vec3};
+pub use chunk::World;
+pub use direction::Direction;
+pub use error::{ChunkError, SeedError, TilesetError};
+pub use grid::{Grid, Lattice};
+pub use heuristic::Heuristic;
+pub use infer::Inference;
+pub use mask::Mask;
+pub use rules::Rules;
+pub use seed::Seeds;
+pub use symmetry::Symmetry;
+pub use topology::{Graph, Topology};
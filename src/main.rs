@@ -1,283 +1,242 @@
-use std::{collections::HashMap, io::Read, path::Path, time::Duration};
+mod cli;
 
-use json::JsonValue;
+use std::{env, error::Error, path::Path, process};
 
-use rand::{seq::SliceRandom, thread_rng};
+use cli::{Command, Format, GenerateArgs, InferArgs, StatsArgs, ValidateArgs, WorldArgs};
+use wfc_tiles::{
+    overlap, seed, validate, AdjacencyMap, Board, CollapseOptions, Grid, Inference, Mask, Tile,
+    TilesetError, WFCAdjacencyMap, World,
+};
 
-const GRID_SIZE: isize = 50;
-
-#[derive(Debug)]
-struct BoardCharacter {
-    pub character: String,
-    pub valid_neighbors: HashMap<String, Vec<String>>,
-}
-
-type AdjacencyMap = HashMap<String, BoardCharacter>;
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let result = match cli::parse(&args) {
+        Ok(Command::Generate(generate)) => run_generate(generate),
+        Ok(Command::Validate(validate)) => run_validate(validate),
+        Ok(Command::Stats(stats)) => run_stats(stats),
+        Ok(Command::Infer(infer)) => run_infer(infer),
+        Ok(Command::World(world)) => run_world(world),
+        Ok(Command::Help) => {
+            println!("{}", cli::USAGE);
+            Ok(())
+        }
+        Err(err) => Err(err.into()),
+    };
 
-trait WFCAdjacencyMap {
-    fn create(prototypes_path: &Path) -> Self;
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
 }
 
-impl WFCAdjacencyMap for AdjacencyMap {
-    fn create(prototypes_path: &Path) -> Self {
-        let mut buffer = String::new();
-        std::fs::File::open(prototypes_path)
-            .unwrap()
-            .read_to_string(&mut buffer)
-            .unwrap();
-        let mut prototypes_json = json::parse(buffer.as_str()).unwrap();
-
-        let mut prototype_map: AdjacencyMap = HashMap::new();
-
-        for (tile_name, tile_description) in prototypes_json.entries_mut() {
-            let character_json: JsonValue = tile_description.remove("char");
-            let character = character_json.as_str().unwrap().to_string();
-
-            let valid_neighbors_json: JsonValue = tile_description.remove("valid_neighbors");
-            let mut valid_neighbors = HashMap::new(); // [Vec<String>; 4] = [Vec::new(), Vec::new(), Vec::new(), Vec::new()];
-            for (direction, valid_neighbor_list) in valid_neighbors_json.entries() {
-                valid_neighbors.insert(direction.to_string(), Vec::new());
-                if let Some(list) = valid_neighbors.get_mut(&direction.to_string()) {
-                    for valid_neighbor_json in valid_neighbor_list.members() {
-                        list.push(valid_neighbor_json.as_str().unwrap().to_string());
-                    }
-                }
+fn run_generate(args: GenerateArgs) -> Result<(), Box<dyn Error>> {
+    let seed = args.seed.unwrap_or_else(rand::random);
+
+    let prototype_map = match &args.sample {
+        Some(sample_path) => {
+            let sample = std::fs::read_to_string(sample_path)
+                .map_err(|err| format!("could not read {}: {}", sample_path.display(), err))?;
+            let prototype_map = overlap::learn(&sample, args.pattern_size, !args.bounded_sample);
+            if prototype_map.is_empty() {
+                return Err(format!(
+                    "{} is too small for {}x{} patterns",
+                    sample_path.display(),
+                    args.pattern_size,
+                    args.pattern_size
+                )
+                .into());
             }
-
-            let board_character = BoardCharacter {
-                character,
-                valid_neighbors,
-            };
-            println!("{} - {:?}", tile_name, board_character.valid_neighbors);
-            prototype_map.insert(tile_name.to_string(), board_character);
+            prototype_map
         }
-        prototype_map
+        None => AdjacencyMap::create(&args.tileset)?,
+    };
+    if prototype_map.grid() == Grid::Graph {
+        return Err(format!(
+            "{} links its tiles as a graph, which has no grid to generate",
+            args.tileset.display()
+        )
+        .into());
     }
-}
-
-type Vec2 = [isize; 2];
-type Domain = Vec<String>;
-
-enum Tile {
-    Collapsed(String),
-    Uncollapsed(Domain),
-}
-
-impl Tile {
-    fn default_domain(prototype_map: &AdjacencyMap) -> Self {
-        let mut default_domain = Vec::new();
-        for (tile_name, _description) in prototype_map.iter() {
-            default_domain.push(tile_name.clone());
+    let mask = match &args.mask {
+        Some(mask_path) => {
+            let source = std::fs::read_to_string(mask_path)
+                .map_err(|err| format!("could not read {}: {}", mask_path.display(), err))?;
+            Some(Mask::parse(&source))
         }
-        Tile::Uncollapsed(default_domain)
+        None => None,
+    };
+    let width = args
+        .width
+        .or(mask.as_ref().map(Mask::width))
+        .unwrap_or(cli::DEFAULT_SIZE);
+    let height = args
+        .height
+        .or(mask.as_ref().map(Mask::height))
+        .unwrap_or(cli::DEFAULT_SIZE);
+
+    let mut board = Board::create_3d(&prototype_map, width, height, args.depth)
+        .with_periodic(args.wrap_x, args.wrap_y);
+    if let Some(mask) = &mask {
+        board = board.with_mask(mask);
+    }
+    if let Some(partial_path) = args.partial {
+        board.seed(&seed::load(&partial_path)?)?;
     }
 
-    fn domain_from(val: String) -> Self {
-        let domain = [val].into();
-        Tile::Uncollapsed(domain)
+    let cells = (width * height * args.depth) as usize;
+    let max_backtracks = args
+        .max_backtracks
+        .unwrap_or(cli::DEFAULT_BACKTRACKS_PER_CELL * cells);
+    let options = CollapseOptions {
+        seed,
+        heuristic: args.heuristic,
+        animate: args.animate,
+        max_backtracks: Some(max_backtracks),
+    };
+
+    // The seed goes to stderr so stdout carries nothing but the board.
+    eprintln!("seed: {}", seed);
+    if !board.collapse(&options) {
+        return Err(format!(
+            "no solution found for seed {} within {} backtracks",
+            seed, max_backtracks
+        )
+        .into());
     }
-}
 
-type Board = HashMap<Vec2, Tile>;
+    let output = match args.format {
+        Format::Text => board.render(),
+        Format::Svg => board.render_svg(),
+        Format::Voxels => board.render_voxels(),
+    };
+    match args.out {
+        Some(out_path) => std::fs::write(out_path, output)?,
+        None => print!("{}", output),
+    }
+    Ok(())
+}
 
-trait WFCBoard {
-    fn create(prototype_map: &AdjacencyMap, grid_size: isize) -> Self;
-    fn get_lowest_entropy(&self) -> Vec2;
-    fn is_collapsed(&self) -> bool;
-    fn propagate_collapse(
-        &mut self,
-        prototype_map: &AdjacencyMap,
-        pos: &Vec2,
-    ) -> Vec<(Vec2, String)>;
-    fn restore_domains(&mut self, tiles: Vec<(Vec2, String)>);
-    fn is_valid_placement(&self, prototype_map: &AdjacencyMap, val: &String, pos: &Vec2) -> bool;
-    fn collapse(&mut self, prototype_map: &AdjacencyMap) -> bool;
-    fn print(&self, prototype_map: &AdjacencyMap, grid_size: isize);
+fn read_tileset(prototypes_path: &Path) -> Result<String, TilesetError> {
+    std::fs::read_to_string(prototypes_path).map_err(|source| TilesetError::Io {
+        path: prototypes_path.to_path_buf(),
+        source,
+    })
 }
 
-impl WFCBoard for Board {
-    fn create(prototype_map: &AdjacencyMap, grid_size: isize) -> Self {
-        let mut board = HashMap::new();
-        for row in 0..grid_size {
-            for col in 0..grid_size {
-                board.insert([row, col], Tile::default_domain(prototype_map));
-            }
+fn run_validate(args: ValidateArgs) -> Result<(), Box<dyn Error>> {
+    let prototypes_path = args.tileset.as_path();
+    let mut source = read_tileset(prototypes_path)?;
+
+    if args.symmetrize {
+        AdjacencyMap::parse_unchecked(&source)?;
+        let mut prototypes_json = json::parse(&source)?;
+        let added = validate::symmetrize(&mut prototypes_json);
+        if added > 0 {
+            source = prototypes_json.pretty(4);
+            std::fs::write(prototypes_path, &source).map_err(|source| TilesetError::Io {
+                path: prototypes_path.to_path_buf(),
+                source,
+            })?;
         }
-
-        board
+        println!("added {} mirrored neighbor entries", added);
     }
 
-    fn get_lowest_entropy(&self) -> Vec2 {
-        let mut lowest_len = usize::MAX;
-        let mut lowest_index = [0, 0];
-
-        for (pos, tile) in self.iter() {
-            match tile {
-                Tile::Collapsed(_) => continue,
-                Tile::Uncollapsed(domain) => {
-                    if domain.len() <= lowest_len {
-                        lowest_len = domain.len();
-                        lowest_index = pos.clone();
-                    }
-                }
-            }
-        }
-
-        lowest_index
+    let issues = validate::validate(&AdjacencyMap::parse_unchecked(&source)?);
+    for issue in issues.iter() {
+        println!("{}", issue);
     }
-
-    fn is_collapsed(&self) -> bool {
-        for (_pos, tile) in self.iter() {
-            if let Tile::Uncollapsed(_) = tile {
-                return false;
-            }
-        }
-
-        true
+    if issues.is_empty() {
+        println!("{}: ok", prototypes_path.display());
+    } else {
+        println!("{}: {} issues", prototypes_path.display(), issues.len());
+        process::exit(1);
     }
+    Ok(())
+}
 
-    fn propagate_collapse(
-        &mut self,
-        prototype_map: &AdjacencyMap,
-        pos: &Vec2,
-    ) -> Vec<(Vec2, String)> {
-        let mut modified = Vec::new();
-
-        let val = match self.get(pos).unwrap() {
-            Tile::Uncollapsed(_) => return modified,
-            Tile::Collapsed(val) => val.clone(),
-        };
-        let pr = pos[0];
-        let pc = pos[1];
-
-        let adjacent = [
-            ("right", [pr - 1, pc]),
-            ("left", [pr + 1, pc]),
-            ("above", [pr, pc - 1]),
-            ("below", [pr, pc + 1]),
-        ];
-
-        for (adjacent_name, adjacent_pos) in adjacent {
-            if let Some(tile) = self.get_mut(&adjacent_pos) {
-                if let Tile::Uncollapsed(domain) = tile {
-                    domain.retain(|adjacent_domain_element| {
-                        // retain adjacent_domain_element if prototype_map[adjacent_domain_element].valid_neighbors["right"].containts(val)
-                        if !prototype_map[adjacent_domain_element].valid_neighbors[adjacent_name]
-                            .contains(&val)
-                        {
-                            modified.push((adjacent_pos, adjacent_domain_element.clone()));
-                            return false;
-                        }
-                        true
-                    })
-                }
-            }
-        }
-
-        modified
+fn run_stats(args: StatsArgs) -> Result<(), Box<dyn Error>> {
+    let prototype_map = AdjacencyMap::parse_unchecked(&read_tileset(&args.tileset)?)?;
+
+    let mut tile_names: Vec<&String> = prototype_map.keys().collect();
+    tile_names.sort();
+    let total_weight: f64 = prototype_map.values().map(|tile| tile.weight).sum();
+
+    println!("{}: {} tiles", args.tileset.display(), tile_names.len());
+    println!();
+    print!(
+        "{:<12} {:>4} {:>8} {:>6}",
+        "tile", "char", "weight", "share"
+    );
+    let directions = prototype_map.grid().directions();
+    for direction in directions {
+        print!(" {:>6}", direction);
     }
-
-    fn restore_domains(&mut self, tiles: Vec<(Vec2, String)>) {
-        for (pos, tile_name) in tiles {
-            if let Some(tile) = self.get_mut(&pos) {
-                if let Tile::Uncollapsed(domain) = tile {
-                    domain.push(tile_name)
-                } else {
-                    self.remove(&pos);
-                    self.insert(pos, Tile::domain_from(tile_name));
-                }
-            }
+    println!();
+    for tile_name in tile_names.iter() {
+        let tile = &prototype_map[*tile_name];
+        print!(
+            "{:<12} {:>4} {:>8.3} {:>5.1}%",
+            tile_name,
+            tile.character,
+            tile.weight,
+            100.0 * tile.weight / total_weight
+        );
+        for direction in directions {
+            let count = tile.valid_neighbors.get(direction).map_or(0, Vec::len);
+            print!(" {:>1$}", count, direction.name().len().max(6));
         }
+        println!();
     }
 
-    fn is_valid_placement(&self, prototype_map: &AdjacencyMap, val: &String, pos: &Vec2) -> bool {
-        let pr = pos[0];
-        let pc = pos[1];
+    let issues = validate::validate(&prototype_map);
+    println!();
+    println!("validation issues: {}", issues.len());
+    Ok(())
+}
 
-        let adjacent = [
-            ("right", [pr - 1, pc]),
-            ("left", [pr + 1, pc]),
-            ("above", [pr, pc - 1]),
-            ("below", [pr, pc + 1]),
-        ];
+fn run_infer(args: InferArgs) -> Result<(), Box<dyn Error>> {
+    let prototype_map = AdjacencyMap::create(&args.tileset)?;
 
-        for (adjacent_name, adjacent_pos) in adjacent {
-            if let Some(tile) = self.get(&adjacent_pos) {
-                if let Tile::Collapsed(adjacent_val) = tile {
-                    if !prototype_map.get(adjacent_val).unwrap().valid_neighbors[adjacent_name]
-                        .contains(val)
-                    {
-                        return false;
-                    }
-                }
-            }
-        }
-
-        true
+    let mut inference = Inference::new(&prototype_map);
+    for example_path in args.examples.iter() {
+        let example = std::fs::read_to_string(example_path)
+            .map_err(|err| format!("could not read {}: {}", example_path.display(), err))?;
+        inference
+            .add(&example)
+            .map_err(|err| format!("{}:{}", example_path.display(), err))?;
     }
 
-    fn collapse(&mut self, prototype_map: &AdjacencyMap) -> bool {
-        self.print(prototype_map, GRID_SIZE);
-        if self.is_collapsed() {
-            return true;
-        }
-
-        let pos = self.get_lowest_entropy();
-        let mut possible_tiles = match self.get(&pos).unwrap() {
-            Tile::Collapsed(_) => panic!("lowest entropy shouldn't be collapsed"),
-            Tile::Uncollapsed(domain) => domain.clone(),
-        };
-
-        possible_tiles.shuffle(&mut thread_rng());
-
-        for possible_tile in possible_tiles.iter() {
-            if self.is_valid_placement(prototype_map, possible_tile, &pos) {
-                let saved_domain = if let Tile::Uncollapsed(domain) = self.get(&pos).unwrap() {
-                    domain.clone()
-                } else {
-                    panic!("lowest entropy tile shouldn't be collapsed")
-                };
-
-                self.remove(&pos);
-                self.insert(pos, Tile::Collapsed(possible_tile.clone()));
-                let modified = self.propagate_collapse(prototype_map, &pos);
-                if self.collapse(prototype_map) {
-                    return true;
-                }
-                self.remove(&pos);
-                self.insert(pos, Tile::Uncollapsed(saved_domain));
-                self.restore_domains(modified);
-            }
-        }
-
-        false
+    let output = inference.to_json().pretty(4);
+    match args.out {
+        Some(out_path) => std::fs::write(out_path, output + "\n")?,
+        None => println!("{}", output),
     }
+    Ok(())
+}
 
-    fn print(&self, prototype_map: &AdjacencyMap, grid_size: isize) {
-        std::thread::sleep(Duration::from_millis(10));
-        print!("\x1B[2J\x1B[1;1H");
-        for c in 0..grid_size {
-            for r in 0..grid_size {
-                let pos = [r, grid_size - c];
-                if let Some(tile) = self.get(&pos) {
-                    match tile {
-                        Tile::Collapsed(tile_name) => {
-                            print!("{}", prototype_map[tile_name].character);
-                        }
-                        Tile::Uncollapsed(_domain) => print!("."),
-                    }
-                }
-            }
-            println!();
+fn run_world(args: WorldArgs) -> Result<(), Box<dyn Error>> {
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let prototype_map = AdjacencyMap::create(&args.tileset)?;
+    let mut world = World::new(&prototype_map, args.chunk_size, args.chunk_size, seed)?
+        .with_heuristic(args.heuristic);
+
+    // The seed goes to stderr so stdout carries nothing but the world.
+    eprintln!("seed: {}", seed);
+    let [chunk_x, chunk_y] = args.chunk;
+    let mut output = String::new();
+    for y in chunk_y * args.chunk_size..(chunk_y + args.rows) * args.chunk_size {
+        for x in chunk_x * args.chunk_size..(chunk_x + args.columns) * args.chunk_size {
+            let Tile::Collapsed(tile_name) = world.get(&[x, y])? else {
+                unreachable!("chunks are fully collapsed");
+            };
+            output.push_str(&prototype_map[&tile_name].character);
         }
+        output.push('\n');
     }
-}
-
-fn main() {
-    let prototype_map = AdjacencyMap::create(Path::new("prototypes.json"));
-    let mut board = Board::create(&prototype_map, GRID_SIZE);
-
-    board.collapse(&prototype_map);
-
-    board.print(&prototype_map, GRID_SIZE);
+    match args.out {
+        Some(out_path) => std::fs::write(out_path, output)?,
+        None => print!("{}", output),
+    }
+    Ok(())
 }
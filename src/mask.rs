@@ -0,0 +1,57 @@
+/// Which cells of a board exist. Cells outside the mask are left out of the board entirely,
+/// so their neighbors treat them like the board edge.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mask {
+    width: isize,
+    height: isize,
+    /// Rows from the top, as drawn.
+    rows: Vec<Vec<bool>>,
+}
+
+impl Mask {
+    /// Parses an ASCII drawing of the board shape, top row first. Spaces and `.` are outside
+    /// the mask and any other character is inside. Short rows are padded with outside cells.
+    pub fn parse(source: &str) -> Self {
+        let rows: Vec<Vec<bool>> = source
+            .lines()
+            .map(|line| line.chars().map(|c| c != ' ' && c != '.').collect())
+            .collect();
+        Mask {
+            width: rows.iter().map(Vec::len).max().unwrap_or(0) as isize,
+            height: rows.len() as isize,
+            rows,
+        }
+    }
+
+    /// A mask of `width` by `height` cells, with the cell at `[x, y]` inside if `inside` says so.
+    pub fn from_fn(width: isize, height: isize, inside: impl Fn(isize, isize) -> bool) -> Self {
+        let rows = (0..height)
+            .map(|y| (0..width).map(|x| inside(x, y)).collect())
+            .collect();
+        Mask {
+            width,
+            height,
+            rows,
+        }
+    }
+
+    pub fn width(&self) -> isize {
+        self.width
+    }
+
+    pub fn height(&self) -> isize {
+        self.height
+    }
+
+    /// Whether the cell at `[x, y]`, in the same screen coordinates as boards, is inside the
+    /// mask.
+    pub fn contains(&self, x: isize, y: isize) -> bool {
+        if x < 0 || y < 0 {
+            return false;
+        }
+        self.rows
+            .get(y as usize)
+            .and_then(|row| row.get(x as usize))
+            .is_some_and(|&inside| inside)
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use crate::{
+    adjacency::{AdjacencyMap, BoardCharacter},
+    grid::Grid,
+};
+
+/// A square block of characters from the sample, rows from the top.
+type Pattern = Vec<Vec<char>>;
+
+/// Learns a tileset from an ASCII sample for the overlapping model: every distinct
+/// `size`×`size` block of the sample becomes a tile named `p0`, `p1`, ... (in order of first
+/// appearance), weighted by how often it occurs and drawn as its top-left character. Two
+/// patterns may be neighbors when they agree wherever they overlap after shifting one of
+/// them by a cell, so a collapsed board reads like the sample.
+///
+/// With `periodic` the sample wraps around, so blocks crossing its right and bottom edges
+/// count too. Short lines are padded with spaces. The result is empty if `size` is 0 or the
+/// sample is too small to hold a single block.
+pub fn learn(sample: &str, size: usize, periodic: bool) -> AdjacencyMap {
+    if size == 0 {
+        return AdjacencyMap::new();
+    }
+
+    let mut rows: Vec<Vec<char>> = sample.lines().map(|line| line.chars().collect()).collect();
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let height = rows.len();
+    for row in rows.iter_mut() {
+        row.resize(width, ' ');
+    }
+
+    let (columns, lines) = if periodic {
+        (width, height)
+    } else {
+        (
+            (width + 1).saturating_sub(size),
+            (height + 1).saturating_sub(size),
+        )
+    };
+    let mut patterns: Vec<Pattern> = Vec::new();
+    let mut counts: HashMap<Pattern, usize> = HashMap::new();
+    for top in 0..lines {
+        for left in 0..columns {
+            let pattern: Pattern = (0..size)
+                .map(|y| {
+                    (0..size)
+                        .map(|x| rows[(top + y) % height][(left + x) % width])
+                        .collect()
+                })
+                .collect();
+            let count = counts.entry(pattern.clone()).or_insert(0);
+            if *count == 0 {
+                patterns.push(pattern);
+            }
+            *count += 1;
+        }
+    }
+
+    let names: Vec<String> = (0..patterns.len()).map(|i| format!("p{}", i)).collect();
+    let mut prototype_map = AdjacencyMap::new();
+    for (pattern, name) in patterns.iter().zip(names.iter()) {
+        let mut valid_neighbors = HashMap::new();
+        for &direction in Grid::Square.directions() {
+            let [dx, dy, _] = direction.offset();
+            let list = patterns
+                .iter()
+                .zip(names.iter())
+                .filter(|(other, _)| agrees(pattern, other, dx, dy))
+                .map(|(_, other_name)| other_name.clone())
+                .collect();
+            valid_neighbors.insert(direction, list);
+        }
+
+        let board_character = BoardCharacter {
+            character: pattern[0][0].to_string(),
+            valid_neighbors,
+            sockets: HashMap::new(),
+            weight: counts[pattern] as f64,
+        };
+        prototype_map.insert(name.clone(), board_character);
+    }
+    prototype_map
+}
+
+/// Whether `other`, placed `dx` columns right and `dy` rows down from `pattern`, matches it
+/// everywhere the two overlap.
+fn agrees(pattern: &Pattern, other: &Pattern, dx: isize, dy: isize) -> bool {
+    let size = pattern.len() as isize;
+    (0..size).all(|y| {
+        (0..size).all(|x| {
+            let (other_x, other_y) = (x - dx, y - dy);
+            other_x < 0
+                || other_y < 0
+                || other_x >= size
+                || other_y >= size
+                || pattern[y as usize][x as usize] == other[other_y as usize][other_x as usize]
+        })
+    })
+}
@@ -0,0 +1,180 @@
+use crate::{
+    adjacency::{AdjacencyMap, WFCAdjacencyMap, EDGE},
+    bitset::{self, BitSet},
+    direction::Direction,
+    topology::Topology,
+};
+
+pub type TileId = usize;
+
+/// A tileset compiled for solving on a topology: tiles are interned to ids (in name order) and
+/// adjacency is stored as one bitset per tile and direction, with each rule checked from the
+/// neighbor's side in the direction the topology says it sees the tile in.
+#[derive(Clone, Debug)]
+pub struct Rules {
+    names: Vec<String>,
+    characters: Vec<String>,
+    weights: Vec<f64>,
+    /// The labels the topology uses, sorted. Their tables follow those of `Direction::ALL`.
+    labels: Vec<&'static str>,
+    /// `compatible[self.slot(direction)][tile]` holds the tiles that may sit in `direction`
+    /// from `tile`.
+    compatible: Vec<Vec<BitSet>>,
+    /// `edge[self.slot(direction)]` holds the tiles allowed on the border on that side, or
+    /// `None` if no tile mentions the edge in that direction.
+    edge: Vec<Option<BitSet>>,
+}
+
+impl Rules {
+    pub fn new<T: Topology>(prototype_map: &AdjacencyMap, topology: &T) -> Self {
+        let mut names: Vec<String> = prototype_map.keys().cloned().collect();
+        names.sort();
+
+        let characters = names
+            .iter()
+            .map(|name| prototype_map[name].character.clone())
+            .collect();
+        let weights = names
+            .iter()
+            .map(|name| prototype_map[name].weight)
+            .collect();
+
+        let mut labels: Vec<&'static str> = topology
+            .directions()
+            .iter()
+            .filter_map(|direction| match direction {
+                Direction::Label(label) => Some(*label),
+                _ => None,
+            })
+            .collect();
+        labels.sort();
+        labels.dedup();
+        let directions: Vec<Direction> = Direction::ALL
+            .into_iter()
+            .chain(labels.iter().map(|&label| Direction::Label(label)))
+            .collect();
+
+        let compatible = directions
+            .iter()
+            .map(|&direction| {
+                let opposite = topology.opposite(direction);
+                names
+                    .iter()
+                    .map(|tile| {
+                        let mut allowed = BitSet::new(names.len());
+                        for (id, neighbor) in names.iter().enumerate() {
+                            if prototype_map.compatible_facing(tile, direction, neighbor, opposite)
+                            {
+                                allowed.insert(id);
+                            }
+                        }
+                        allowed
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let edge = directions
+            .iter()
+            .map(|&direction| {
+                let mut allowed = BitSet::new(names.len());
+                for (id, tile) in names.iter().enumerate() {
+                    if prototype_map[tile].allows(direction, EDGE) {
+                        allowed.insert(id);
+                    }
+                }
+                (!allowed.is_empty()).then_some(allowed)
+            })
+            .collect();
+
+        Rules {
+            names,
+            characters,
+            weights,
+            labels,
+            compatible,
+            edge,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Number of `u64` words in every domain of this tileset.
+    pub fn words(&self) -> usize {
+        bitset::words_for(self.len())
+    }
+
+    pub fn id(&self, name: &str) -> Option<TileId> {
+        self.names
+            .binary_search_by(|probe| probe.as_str().cmp(name))
+            .ok()
+    }
+
+    pub fn name(&self, id: TileId) -> &str {
+        &self.names[id]
+    }
+
+    pub fn character(&self, id: TileId) -> &str {
+        &self.characters[id]
+    }
+
+    pub fn weight(&self, id: TileId) -> f64 {
+        self.weights[id]
+    }
+
+    /// Position of `direction`'s tables.
+    ///
+    /// Panics on a label the topology doesn't use.
+    fn slot(&self, direction: Direction) -> usize {
+        match direction {
+            Direction::Label(label) => {
+                let position = self
+                    .labels
+                    .binary_search(&label)
+                    .unwrap_or_else(|_| panic!("the topology has no \"{}\" links", label));
+                Direction::ALL.len() + position
+            }
+            _ => direction.index(),
+        }
+    }
+
+    pub fn compatible(&self, direction: Direction, tile: TileId) -> &BitSet {
+        &self.compatible[self.slot(direction)][tile]
+    }
+
+    /// The tiles that may sit on the border with nothing in `direction`, if restricted at all.
+    pub fn edge(&self, direction: Direction) -> Option<&BitSet> {
+        self.edge[self.slot(direction)].as_ref()
+    }
+
+    /// Writes into `allowed` every tile that may sit in `direction` from some tile in `domain`.
+    pub fn support(&self, direction: Direction, domain: &[u64], allowed: &mut [u64]) {
+        allowed.fill(0);
+        for tile in bitset::ones(domain) {
+            for (word, compatible) in allowed
+                .iter_mut()
+                .zip(self.compatible(direction, tile).words())
+            {
+                *word |= compatible;
+            }
+        }
+    }
+
+    /// Shannon entropy of the tile weights in `domain`.
+    pub fn entropy(&self, domain: &[u64]) -> f64 {
+        let mut weight_sum = 0.0;
+        let mut weight_log_weight_sum = 0.0;
+        for tile in bitset::ones(domain) {
+            let weight = self.weights[tile];
+            weight_sum += weight;
+            weight_log_weight_sum += weight * weight.ln();
+        }
+        weight_sum.ln() - weight_log_weight_sum / weight_sum
+    }
+}
@@ -0,0 +1,100 @@
+use std::path::Path;
+
+use json::JsonValue;
+
+use crate::{
+    board::{Tile, Vec3},
+    error::SeedError,
+};
+
+/// A partial board: cells fixed to one tile (`Tile::Collapsed`) or narrowed to a set of
+/// candidates (`Tile::Uncollapsed`), applied with `Board::seed` before collapsing.
+pub type Seeds = Vec<(Vec3, Tile)>;
+
+pub fn load(seeds_path: &Path) -> Result<Seeds, SeedError> {
+    let source = std::fs::read_to_string(seeds_path).map_err(|source| SeedError::Io {
+        path: seeds_path.to_path_buf(),
+        source,
+    })?;
+    parse(&source)
+}
+
+/// Parses a partial board from JSON: an array of cells, each with a `pos` of `[x, y]` board
+/// coordinates (column from the left, row from the top) and either a `tile` name to fix or a
+/// `domain` list of allowed tile names. Cells on a 3D board add the layer, as `[x, y, z]`;
+/// two coordinates mean the bottom layer.
+///
+/// ```json
+/// [
+///     { "pos": [0, 0], "tile": "tl" },
+///     { "pos": [4, 2], "domain": ["s", "v"] }
+/// ]
+/// ```
+pub fn parse(source: &str) -> Result<Seeds, SeedError> {
+    let seeds_json = json::parse(source)?;
+    if !seeds_json.is_array() {
+        return Err(wrong_type("$".to_string(), "array"));
+    }
+
+    let mut seeds = Vec::new();
+    for (i, seed_json) in seeds_json.members().enumerate() {
+        let seed_path = format!("$[{}]", i);
+        if !seed_json.is_object() {
+            return Err(wrong_type(seed_path, "object"));
+        }
+
+        let pos_json = expect_field(&seed_path, seed_json, "pos")?;
+        let pos_path = format!("{}.pos", seed_path);
+        if !pos_json.is_array() || !(2..=3).contains(&pos_json.len()) {
+            return Err(wrong_type(pos_path, "[x, y] or [x, y, z]"));
+        }
+        let mut pos = [0; 3];
+        for (axis, coordinate) in pos_json.members().enumerate() {
+            pos[axis] = coordinate
+                .as_isize()
+                .ok_or_else(|| wrong_type(format!("{}[{}]", pos_path, axis), "integer"))?;
+        }
+
+        let tile = if seed_json.has_key("tile") {
+            let tile_name = seed_json["tile"]
+                .as_str()
+                .ok_or_else(|| wrong_type(format!("{}.tile", seed_path), "string"))?;
+            Tile::Collapsed(tile_name.to_string())
+        } else {
+            let domain_json = expect_field(&seed_path, seed_json, "domain")?;
+            let domain_path = format!("{}.domain", seed_path);
+            if !domain_json.is_array() {
+                return Err(wrong_type(domain_path, "array"));
+            }
+            let mut domain = Vec::new();
+            for (j, tile_json) in domain_json.members().enumerate() {
+                let tile_name = tile_json
+                    .as_str()
+                    .ok_or_else(|| wrong_type(format!("{}[{}]", domain_path, j), "string"))?;
+                domain.push(tile_name.to_string());
+            }
+            Tile::Uncollapsed(domain)
+        };
+
+        seeds.push((pos, tile));
+    }
+    Ok(seeds)
+}
+
+fn wrong_type(path: String, expected: &'static str) -> SeedError {
+    SeedError::WrongType { path, expected }
+}
+
+fn expect_field<'a>(
+    seed_path: &str,
+    seed_json: &'a JsonValue,
+    field: &str,
+) -> Result<&'a JsonValue, SeedError> {
+    if seed_json.has_key(field) {
+        Ok(&seed_json[field])
+    } else {
+        Err(SeedError::MissingField {
+            path: format!("{}.{}", seed_path, field),
+        })
+    }
+}
@@ -0,0 +1,308 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use crate::{
+    adjacency::{AdjacencyMap, BoardCharacter},
+    direction::Direction,
+};
+
+/// Separates a tile's name from the index of one of its generated variants, as in `corner@2`.
+/// Variant 0 keeps the plain name.
+pub const VARIANT_SEPARATOR: char = '@';
+
+/// Which rotations and reflections of a tile look different, named after the letter with the
+/// same symmetry. Variants are numbered by counter-clockwise quarter turns, with the mirrored
+/// variants of `F` after the rotated ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Symmetry {
+    /// Looks the same however it is turned, like `╬` or a blank tile.
+    X,
+    /// Two variants, each symmetric under reflection, like `║` and `═`.
+    I,
+    /// Two variants that reflection swaps, like `\` and `/`.
+    Backslash,
+    /// Four variants, each symmetric under one reflection, like `╦`. The base tile has to be
+    /// the one that is symmetric left to right, as `╦` is and `╠` isn't.
+    T,
+    /// Four variants, each reflecting into a neighboring rotation, like `╚`. The base tile has
+    /// to be oriented like `╚`, so that its mirror image is its next variant, `╝`; a `╔` base
+    /// would get its variants' rules mirrored the wrong way.
+    L,
+    /// Eight variants, with no symmetry at all.
+    F,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 6] = [
+        Symmetry::X,
+        Symmetry::I,
+        Symmetry::Backslash,
+        Symmetry::T,
+        Symmetry::L,
+        Symmetry::F,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Symmetry::X => "X",
+            Symmetry::I => "I",
+            Symmetry::Backslash => "\\",
+            Symmetry::T => "T",
+            Symmetry::L => "L",
+            Symmetry::F => "F",
+        }
+    }
+
+    /// Number of distinct variants.
+    pub fn cardinality(&self) -> usize {
+        match self {
+            Symmetry::X => 1,
+            Symmetry::I | Symmetry::Backslash => 2,
+            Symmetry::T | Symmetry::L => 4,
+            Symmetry::F => 8,
+        }
+    }
+
+    /// The variant that `variant` becomes when turned a quarter counter-clockwise.
+    pub fn rotate(&self, variant: usize) -> usize {
+        match self {
+            Symmetry::X => variant,
+            Symmetry::I | Symmetry::Backslash => 1 - variant,
+            Symmetry::T | Symmetry::L => (variant + 1) % 4,
+            Symmetry::F if variant < 4 => (variant + 1) % 4,
+            Symmetry::F => 4 + (variant + 3) % 4,
+        }
+    }
+
+    /// The variant that `variant` becomes when mirrored left to right.
+    pub fn reflect(&self, variant: usize) -> usize {
+        match self {
+            Symmetry::X | Symmetry::I => variant,
+            Symmetry::Backslash => 1 - variant,
+            Symmetry::T => (4 - variant) % 4,
+            Symmetry::L => variant ^ 1,
+            Symmetry::F => (variant + 4) % 8,
+        }
+    }
+}
+
+impl fmt::Display for Symmetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Symmetry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Symmetry::ALL
+            .into_iter()
+            .find(|symmetry| symmetry.name() == s)
+            .ok_or_else(|| format!("unknown symmetry \"{}\"", s))
+    }
+}
+
+/// One of the eight ways to turn a tile over: an optional left-right mirror followed by
+/// `rotations` counter-clockwise quarter turns.
+#[derive(Clone, Copy)]
+struct Transform {
+    reflect: bool,
+    rotations: usize,
+}
+
+impl Transform {
+    fn all() -> impl Iterator<Item = Transform> {
+        [false, true]
+            .into_iter()
+            .flat_map(|reflect| (0..4).map(move |rotations| Transform { reflect, rotations }))
+    }
+
+    fn variant(&self, symmetry: Symmetry, mut variant: usize) -> usize {
+        if self.reflect {
+            variant = symmetry.reflect(variant);
+        }
+        for _ in 0..self.rotations {
+            variant = symmetry.rotate(variant);
+        }
+        variant
+    }
+
+    fn direction(&self, mut direction: Direction) -> Direction {
+        if self.reflect && matches!(direction, Direction::Right | Direction::Left) {
+            direction = direction.opposite();
+        }
+        for _ in 0..self.rotations {
+            direction = match direction {
+                Direction::Right => Direction::Above,
+                Direction::Above => Direction::Left,
+                Direction::Left => Direction::Below,
+                Direction::Below => Direction::Right,
+                // Tiles turn about the vertical axis, so up stays up, and graph links have no
+                // side to turn.
+                Direction::Up | Direction::Down | Direction::Adjacent | Direction::Label(_) => {
+                    direction
+                }
+                _ => unreachable!("symmetric tiles are square or cubes"),
+            };
+        }
+        direction
+    }
+
+    fn character(&self, character: &str) -> String {
+        character
+            .chars()
+            .map(|mut c| {
+                if self.reflect {
+                    c = mirror_char(c);
+                }
+                for _ in 0..self.rotations {
+                    c = rotate_char(c);
+                }
+                c
+            })
+            .collect()
+    }
+}
+
+/// Characters that turn into each other, each group in counter-clockwise order.
+const ROTATIONS: [&str; 10] = [
+    "═║",
+    "─│",
+    "╔╚╝╗",
+    "┌└┘┐",
+    "╦╠╩╣",
+    "┬├┴┤",
+    "/\\",
+    "→↑←↓",
+    ">^<v",
+    "-|",
+];
+
+/// Pairs of characters that are mirror images of each other, left to right.
+const MIRRORS: [&str; 9] = ["╔╗", "╚╝", "┌┐", "└┘", "╠╣", "├┤", "/\\", "→←", "><"];
+
+fn rotate_char(c: char) -> char {
+    for group in ROTATIONS {
+        let chars: Vec<char> = group.chars().collect();
+        if let Some(i) = chars.iter().position(|&other| other == c) {
+            return chars[(i + 1) % chars.len()];
+        }
+    }
+    c
+}
+
+fn mirror_char(c: char) -> char {
+    for pair in MIRRORS {
+        let chars: Vec<char> = pair.chars().collect();
+        if let Some(i) = chars.iter().position(|&other| other == c) {
+            return chars[1 - i];
+        }
+    }
+    c
+}
+
+pub fn variant_name(tile: &str, variant: usize) -> String {
+    if variant == 0 {
+        tile.to_string()
+    } else {
+        format!("{}{}{}", tile, VARIANT_SEPARATOR, variant)
+    }
+}
+
+/// A tile with a `symmetry` field, before its variants are generated.
+pub struct SymmetricTile {
+    pub name: String,
+    pub symmetry: Symmetry,
+    /// Explicit characters for each variant, overriding the built-in rotation table.
+    pub chars: Option<Vec<String>>,
+}
+
+/// Replaces every symmetric tile in `prototype_map` with its variants. A variant's rules are
+/// the base tile's rules turned the same way, with each neighbor turned along with it;
+/// neighbors without a `symmetry` are left as they are. Fails with the name of a variant
+/// that is already defined.
+pub fn expand(
+    prototype_map: &mut AdjacencyMap,
+    symmetric_tiles: &[SymmetricTile],
+) -> Result<(), String> {
+    let symmetries: HashMap<&str, Symmetry> = symmetric_tiles
+        .iter()
+        .map(|tile| (tile.name.as_str(), tile.symmetry))
+        .collect();
+
+    let turn_reference = |transform: &Transform, reference: &str| -> String {
+        let (base, variant) = match reference.rsplit_once(VARIANT_SEPARATOR) {
+            Some((base, variant)) if symmetries.contains_key(base) => {
+                match variant.parse::<usize>() {
+                    Ok(variant) => (base, variant),
+                    Err(_) => return reference.to_string(),
+                }
+            }
+            _ => (reference, 0),
+        };
+        match symmetries.get(base) {
+            Some(&symmetry) if variant < symmetry.cardinality() => {
+                variant_name(base, transform.variant(symmetry, variant))
+            }
+            _ => reference.to_string(),
+        }
+    };
+
+    let mut variants = Vec::new();
+    for tile in symmetric_tiles {
+        let base = &prototype_map[&tile.name];
+        for variant in 0..tile.symmetry.cardinality() {
+            let mut valid_neighbors: HashMap<Direction, Vec<String>> = HashMap::new();
+            let mut sockets = HashMap::new();
+            let mut character = None;
+            for transform in Transform::all().filter(|t| t.variant(tile.symmetry, 0) == variant) {
+                character.get_or_insert_with(|| transform.character(&base.character));
+                // Mirroring a tile reverses the order its sides are read in.
+                for (direction, socket) in base.sockets.iter() {
+                    let turned = if transform.reflect {
+                        socket.mirrored()
+                    } else {
+                        socket.clone()
+                    };
+                    sockets
+                        .entry(transform.direction(*direction))
+                        .or_insert(turned);
+                }
+                for (direction, list) in base.valid_neighbors.iter() {
+                    let turned_list = valid_neighbors
+                        .entry(transform.direction(*direction))
+                        .or_default();
+                    for reference in list {
+                        let turned = turn_reference(&transform, reference);
+                        if !turned_list.contains(&turned) {
+                            turned_list.push(turned);
+                        }
+                    }
+                }
+            }
+
+            let character = match &tile.chars {
+                Some(chars) => chars[variant].clone(),
+                None => character.unwrap_or_else(|| base.character.clone()),
+            };
+            variants.push((
+                variant,
+                variant_name(&tile.name, variant),
+                BoardCharacter {
+                    character,
+                    valid_neighbors,
+                    sockets,
+                    weight: base.weight,
+                },
+            ));
+        }
+    }
+
+    for (variant, name, board_character) in variants {
+        if variant > 0 && prototype_map.contains_key(&name) {
+            return Err(name);
+        }
+        prototype_map.insert(name, board_character);
+    }
+    Ok(())
+}
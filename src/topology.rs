@@ -0,0 +1,109 @@
+use crate::direction::Direction;
+
+/// The cells of a board and how they neighbor each other. `Board` runs the solver on any
+/// topology: `Lattice` covers the square, hex and 3D grids, and `Graph` any other shape, like
+/// the rooms of a level or the regions of a Voronoi map.
+pub trait Topology {
+    /// The number of cells, which are numbered from 0.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether `cell` is part of the board. Cells that aren't have no neighbors and are never
+    /// collapsed.
+    fn contains(&self, _cell: usize) -> bool {
+        true
+    }
+
+    /// Every direction a cell can have a neighbor in. A cell without a neighbor in one of
+    /// them is on the edge on that side, for edge rules.
+    fn directions(&self) -> &[Direction];
+
+    /// The neighbors of `cell` that are part of the board, with their direction from it.
+    fn neighbors(&self, cell: usize) -> Vec<(Direction, usize)>;
+
+    /// The direction a neighbor in `direction` sees the cell in, which is how tilesets check
+    /// their rules both ways.
+    fn opposite(&self, direction: Direction) -> Direction {
+        direction.opposite()
+    }
+
+    /// The board as text, given the character drawn for every cell that is part of it.
+    fn render(&self, characters: &[&str]) -> String;
+}
+
+/// A topology made of explicit links between cells. Links are labelled with the direction
+/// from one cell to the other: `Direction::Adjacent` when the side doesn't matter, or a
+/// `Direction::Label` of the graph's own.
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    neighbors: Vec<Vec<(Direction, usize)>>,
+    directions: Vec<Direction>,
+    /// Directions paired with `pair`, both ways round.
+    opposites: Vec<(Direction, Direction)>,
+}
+
+impl Graph {
+    /// `cells` cells with no links between them.
+    pub fn new(cells: usize) -> Self {
+        Graph {
+            neighbors: vec![Vec::new(); cells],
+            directions: Vec::new(),
+            opposites: Vec::new(),
+        }
+    }
+
+    /// Makes `direction` and `opposite` each other's opposites, so that e.g. a room's
+    /// `north` door leads to the `south` door of the next. Only affects later links.
+    pub fn pair(&mut self, direction: Direction, opposite: Direction) {
+        self.opposites
+            .retain(|&(from, _)| from != direction && from != opposite);
+        self.opposites.push((direction, opposite));
+        self.opposites.push((opposite, direction));
+    }
+
+    /// Links `cell` to `neighbor`, which lies in `direction` from it and sees `cell` in the
+    /// opposite direction.
+    pub fn connect(&mut self, cell: usize, direction: Direction, neighbor: usize) {
+        let opposite = self.opposite(direction);
+        self.neighbors[cell].push((direction, neighbor));
+        self.neighbors[neighbor].push((opposite, cell));
+        for direction in [direction, opposite] {
+            if !self.directions.contains(&direction) {
+                self.directions.push(direction);
+            }
+        }
+    }
+}
+
+impl Topology for Graph {
+    fn len(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    fn directions(&self) -> &[Direction] {
+        &self.directions
+    }
+
+    fn neighbors(&self, cell: usize) -> Vec<(Direction, usize)> {
+        self.neighbors[cell].clone()
+    }
+
+    fn opposite(&self, direction: Direction) -> Direction {
+        self.opposites
+            .iter()
+            .find(|&&(from, _)| from == direction)
+            .map_or(direction.opposite(), |&(_, opposite)| opposite)
+    }
+
+    /// One line per cell: its number and its character.
+    fn render(&self, characters: &[&str]) -> String {
+        characters
+            .iter()
+            .enumerate()
+            .map(|(cell, character)| format!("{} {}\n", cell, character))
+            .collect()
+    }
+}
@@ -0,0 +1,238 @@
+use std::{collections::HashSet, fmt};
+
+use json::JsonValue;
+
+use crate::{
+    adjacency::{AdjacencyMap, WFCAdjacencyMap, EDGE},
+    direction::Direction,
+    grid::Grid,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Issue {
+    /// `tile` lists `reference` as a neighbor, but no such tile is defined.
+    DanglingReference {
+        tile: String,
+        direction: Direction,
+        reference: String,
+    },
+    /// `tile` has neither a `valid_neighbors` list nor a socket for `direction`, so only
+    /// tiles that list it themselves may ever sit there.
+    MissingDirection { tile: String, direction: Direction },
+    /// `tile` allows `neighbor` in `direction`, but `neighbor` doesn't allow `tile` in the
+    /// opposite direction.
+    Asymmetric {
+        tile: String,
+        direction: Direction,
+        neighbor: String,
+    },
+    /// `tile` can't be surrounded on all sides by compatible tiles (even after removing every
+    /// other such tile), so it can only ever appear on the edge of a board.
+    Unplaceable { tile: String },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::DanglingReference {
+                tile,
+                direction,
+                reference,
+            } => write!(
+                f,
+                "{}: {} neighbor \"{}\" is not a defined tile",
+                tile, direction, reference
+            ),
+            Issue::MissingDirection { tile, direction } => {
+                write!(
+                    f,
+                    "{}: no valid_neighbors or socket for \"{}\"",
+                    tile, direction
+                )
+            }
+            Issue::Asymmetric {
+                tile,
+                direction,
+                neighbor,
+            } => write!(
+                f,
+                "{}: allows \"{}\" {} it, but \"{}\" does not allow \"{}\" {} it",
+                tile,
+                neighbor,
+                relation(*direction),
+                neighbor,
+                tile,
+                relation(direction.opposite())
+            ),
+            Issue::Unplaceable { tile } => {
+                write!(f, "{}: can never be placed away from the board edge", tile)
+            }
+        }
+    }
+}
+
+fn relation(direction: Direction) -> &'static str {
+    match direction {
+        Direction::Right => "to the right of",
+        Direction::Left => "to the left of",
+        Direction::Above => "above",
+        Direction::Below => "below",
+        Direction::AboveRight => "above and to the right of",
+        Direction::AboveLeft => "above and to the left of",
+        Direction::BelowRight => "below and to the right of",
+        Direction::BelowLeft => "below and to the left of",
+        Direction::Up => "on top of",
+        Direction::Down => "underneath",
+        Direction::Adjacent => "next to",
+        Direction::Label(_) => "linked to",
+    }
+}
+
+/// Checks a tileset (usually loaded with `parse_unchecked`) for referential integrity,
+/// missing directions, asymmetric rules and unplaceable tiles.
+///
+/// Which label a graph pairs with which is up to the graph, so rules in labelled directions
+/// are only checked for references and missing directions.
+pub fn validate(prototype_map: &AdjacencyMap) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    let mut tile_names: Vec<&String> = prototype_map.keys().collect();
+    tile_names.sort();
+
+    for &tile_name in tile_names.iter() {
+        let valid_neighbors = &prototype_map[tile_name].valid_neighbors;
+        let sockets = &prototype_map[tile_name].sockets;
+        for direction in directions(prototype_map) {
+            let Some(valid_neighbor_list) = valid_neighbors.get(&direction) else {
+                if sockets.contains_key(&direction) {
+                    continue;
+                }
+                issues.push(Issue::MissingDirection {
+                    tile: tile_name.clone(),
+                    direction,
+                });
+                continue;
+            };
+            for neighbor in valid_neighbor_list
+                .iter()
+                .filter(|&neighbor| neighbor != EDGE)
+            {
+                match prototype_map.get(neighbor) {
+                    None => issues.push(Issue::DanglingReference {
+                        tile: tile_name.clone(),
+                        direction,
+                        reference: neighbor.clone(),
+                    }),
+                    Some(neighbor_character) => {
+                        if !matches!(direction, Direction::Label(_))
+                            && !neighbor_character.allows(direction.opposite(), tile_name)
+                        {
+                            issues.push(Issue::Asymmetric {
+                                tile: tile_name.clone(),
+                                direction,
+                                neighbor: neighbor.clone(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let placeable = placeable_tiles(prototype_map);
+    for &tile_name in tile_names.iter() {
+        if !placeable.contains(tile_name.as_str()) {
+            issues.push(Issue::Unplaceable {
+                tile: tile_name.clone(),
+            });
+        }
+    }
+
+    issues
+}
+
+/// The directions cells have neighbors in on the tileset's grid, or for a graph, every
+/// direction its tiles use.
+fn directions(prototype_map: &AdjacencyMap) -> Vec<Direction> {
+    let grid = prototype_map.grid();
+    if grid != Grid::Graph {
+        return grid.directions().to_vec();
+    }
+    let mut directions: Vec<Direction> = prototype_map
+        .values()
+        .flat_map(|tile| tile.valid_neighbors.keys().chain(tile.sockets.keys()))
+        .copied()
+        .collect();
+    directions.sort();
+    directions.dedup();
+    directions
+}
+
+/// Repeatedly discards tiles that lack a mutually compatible neighbor in some direction
+/// other than a label, returning the tiles left at the fixpoint.
+fn placeable_tiles(prototype_map: &AdjacencyMap) -> HashSet<&str> {
+    let mut placeable: HashSet<&str> = prototype_map.keys().map(String::as_str).collect();
+    let directions: Vec<Direction> = directions(prototype_map)
+        .into_iter()
+        .filter(|direction| !matches!(direction, Direction::Label(_)))
+        .collect();
+
+    loop {
+        let unsupported: Vec<&str> = placeable
+            .iter()
+            .copied()
+            .filter(|&tile_name| {
+                directions.iter().any(|&direction| {
+                    !placeable
+                        .iter()
+                        .any(|&neighbor| prototype_map.compatible(tile_name, direction, neighbor))
+                })
+            })
+            .collect();
+
+        if unsupported.is_empty() {
+            return placeable;
+        }
+        for tile_name in unsupported {
+            placeable.remove(tile_name);
+        }
+    }
+}
+
+/// Mirrors every rule in the raw prototypes JSON: if A allows B to its right, B is made to
+/// allow A to its left. Missing direction lists are created and references to undefined tiles
+/// (including the board edge) are left alone, as are labels, which only a graph pairs up.
+/// Returns the number of entries added.
+pub fn symmetrize(prototypes_json: &mut JsonValue) -> usize {
+    let mut additions = Vec::new();
+    for (tile_name, tile_description) in prototypes_json.entries() {
+        for direction in Direction::ALL {
+            for neighbor in tile_description["valid_neighbors"][direction.name()].members() {
+                let Some(neighbor) = neighbor.as_str() else {
+                    continue;
+                };
+                let opposite = direction.opposite().name();
+                let mirrored = &prototypes_json[neighbor]["valid_neighbors"][opposite];
+                if prototypes_json[neighbor].is_object() && !mirrored.contains(tile_name) {
+                    additions.push((neighbor.to_string(), opposite, tile_name.to_string()));
+                }
+            }
+        }
+    }
+
+    let mut added = 0;
+    for (neighbor, direction, tile_name) in additions {
+        let valid_neighbors = &mut prototypes_json[neighbor.as_str()]["valid_neighbors"];
+        if valid_neighbors.is_null() {
+            *valid_neighbors = JsonValue::new_object();
+        }
+        let list = &mut valid_neighbors[direction];
+        if list.is_null() {
+            *list = JsonValue::new_array();
+        }
+        if !list.contains(tile_name.as_str()) && list.push(tile_name).is_ok() {
+            added += 1;
+        }
+    }
+    added
+}
@@ -0,0 +1,135 @@
+use std::path::Path;
+
+use wfc_tiles::{AdjacencyMap, ChunkError, Direction, Tile, WFCAdjacencyMap, World};
+
+/// Water, sand, grass and forest, each allowed next to itself and the ones before and after
+/// it, so that a chunk can be filled in between any borders.
+fn terrain() -> AdjacencyMap {
+    let tiles = [
+        ("water", "~", r#"["water", "sand"]"#),
+        ("sand", ".", r#"["water", "sand", "grass"]"#),
+        ("grass", ",", r#"["sand", "grass", "forest"]"#),
+        ("forest", "T", r#"["grass", "forest"]"#),
+    ];
+    let tiles: Vec<String> = tiles
+        .iter()
+        .map(|(name, character, list)| {
+            format!(
+                r#""{}": {{
+                    "char": "{}",
+                    "valid_neighbors": {{ "right": {}, "left": {}, "above": {}, "below": {} }}
+                }}"#,
+                name, character, list, list, list, list
+            )
+        })
+        .collect();
+    AdjacencyMap::parse(&format!("{{ {} }}", tiles.join(", "))).unwrap()
+}
+
+/// The area the tests look at: a few chunks in every direction from the origin.
+const AREA: [std::ops::Range<isize>; 2] = [-9..15, -8..12];
+
+/// The tile names in `AREA` in reading order, after generating its chunks in reading order
+/// or the reverse.
+fn area(world: &mut World, reversed: bool) -> Vec<String> {
+    let [xs, ys] = AREA;
+    let positions: Vec<[isize; 2]> = ys.flat_map(|y| xs.clone().map(move |x| [x, y])).collect();
+    if reversed {
+        for pos in positions.iter().rev() {
+            world.get(pos).unwrap();
+        }
+    }
+    positions
+        .iter()
+        .map(|pos| match world.get(pos).unwrap() {
+            Tile::Collapsed(name) => name,
+            Tile::Uncollapsed(_) => panic!("chunks are fully collapsed"),
+        })
+        .collect()
+}
+
+/// Checks that every tile in `names`, read from `AREA`, fits its neighbors to the right and
+/// below.
+fn assert_seams_fit(prototype_map: &AdjacencyMap, names: &[String]) {
+    let [xs, ys] = AREA;
+    let width = xs.len();
+    for (i, name) in names.iter().enumerate() {
+        if i % width + 1 < width {
+            let right = &names[i + 1];
+            assert!(prototype_map.compatible(name, Direction::Right, right));
+        }
+        if i + width < names.len() {
+            let below = &names[i + width];
+            assert!(prototype_map.compatible(name, Direction::Below, below));
+        }
+    }
+    assert_eq!(names.len(), width * ys.len());
+}
+
+#[test]
+fn seams_are_consistent() {
+    let prototype_map = terrain();
+    let mut world = World::new(&prototype_map, 5, 4, 7).unwrap();
+    assert_seams_fit(&prototype_map, &area(&mut world, false));
+}
+
+#[test]
+fn generation_order_does_not_matter() {
+    let prototype_map = terrain();
+    let forwards = area(&mut World::new(&prototype_map, 5, 4, 7).unwrap(), false);
+    let backwards = area(&mut World::new(&prototype_map, 5, 4, 7).unwrap(), true);
+    assert_eq!(forwards, backwards);
+
+    let other_seed = area(&mut World::new(&prototype_map, 5, 4, 8).unwrap(), false);
+    assert_ne!(forwards, other_seed);
+}
+
+#[test]
+fn forgotten_chunks_come_back_the_same() {
+    let prototype_map = terrain();
+    let mut world = World::new(&prototype_map, 5, 4, 7).unwrap();
+    let before = area(&mut world, false);
+
+    // Forget every other chunk first, so they are regenerated next to remembered ones.
+    let [xs, ys] = AREA;
+    let mut chunks = Vec::new();
+    for y in ys {
+        for x in xs.clone() {
+            let (chunk, _) = world.locate(&[x, y]);
+            if !chunks.contains(&chunk) {
+                chunks.push(chunk);
+            }
+        }
+    }
+    for chunk in chunks.iter().step_by(2) {
+        world.forget(*chunk);
+    }
+    assert_eq!(area(&mut world, true), before);
+
+    for chunk in &chunks {
+        world.forget(*chunk);
+    }
+    assert_eq!(area(&mut world, true), before);
+}
+
+#[test]
+fn chunks_must_have_cells() {
+    let prototype_map = terrain();
+    for (width, height) in [(0, 4), (4, 0), (-1, 4)] {
+        assert!(matches!(
+            World::new(&prototype_map, width, height, 7),
+            Err(ChunkError::InvalidSize { .. })
+        ));
+    }
+}
+
+#[test]
+fn closed_loops_can_be_chunked() {
+    // Pipes form closed loops, so a chunk can only be filled in if an even number of pipe ends
+    // cross into it from its neighbors.
+    let prototype_map = AdjacencyMap::create(Path::new("prototypes.json")).unwrap();
+    for seed in 0..4 {
+        let mut world = World::new(&prototype_map, 8, 8, seed).unwrap();
+        assert_seams_fit(&prototype_map, &area(&mut world, seed % 2 == 1));
+    }
+}
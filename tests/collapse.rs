@@ -0,0 +1,82 @@
+use std::path::Path;
+
+use wfc_tiles::{AdjacencyMap, Board, CollapseOptions, Heuristic, TilesetError, WFCAdjacencyMap};
+
+/// Two tiles that fit anywhere, with the given weights.
+fn weighted(a: &str, b: &str) -> Result<AdjacencyMap, TilesetError> {
+    let any =
+        r#"{ "right": ["a", "b"], "left": ["a", "b"], "above": ["a", "b"], "below": ["a", "b"] }"#;
+    AdjacencyMap::parse(&format!(
+        r#"{{
+            "a": {{ "char": "A", "weight": {}, "valid_neighbors": {} }},
+            "b": {{ "char": "B", "weight": {}, "valid_neighbors": {} }}
+        }}"#,
+        a, any, b, any
+    ))
+}
+
+fn solve(prototype_map: &AdjacencyMap, options: &CollapseOptions) -> String {
+    let mut board = Board::create(prototype_map, 24, 16);
+    assert!(board.collapse(options));
+    board.render()
+}
+
+#[test]
+fn same_seed_gives_the_same_board() {
+    let prototype_map = AdjacencyMap::create(Path::new("prototypes.json")).unwrap();
+    for heuristic in Heuristic::ALL {
+        let options = CollapseOptions {
+            seed: 3,
+            heuristic,
+            ..CollapseOptions::default()
+        };
+        assert_eq!(
+            solve(&prototype_map, &options),
+            solve(&prototype_map, &options),
+            "{}",
+            heuristic
+        );
+    }
+}
+
+#[test]
+fn different_seeds_give_different_boards() {
+    let prototype_map = AdjacencyMap::create(Path::new("prototypes.json")).unwrap();
+    let boards: Vec<String> = (0..4)
+        .map(|seed| {
+            let options = CollapseOptions {
+                seed,
+                ..CollapseOptions::default()
+            };
+            solve(&prototype_map, &options)
+        })
+        .collect();
+    for (i, board) in boards.iter().enumerate() {
+        assert!(!boards[..i].contains(board), "seed {} repeats a board", i);
+    }
+}
+
+#[test]
+fn weights_must_be_positive() {
+    for weight in ["0", "-1", "\"heavy\""] {
+        match weighted(weight, "1") {
+            Err(TilesetError::WrongType { path, expected, .. }) => {
+                assert_eq!(path, "$.a.weight");
+                assert_eq!(expected, "positive number");
+            }
+            other => panic!("weight {} gave {:?}", weight, other.map(|_| ())),
+        }
+    }
+    assert!(weighted("0.5", "2").is_ok());
+}
+
+#[test]
+fn heavier_tiles_are_picked_more_often() {
+    let count_a = |prototype_map: &AdjacencyMap| {
+        let render = solve(prototype_map, &CollapseOptions::default());
+        render.matches('A').count()
+    };
+    let cells = 24 * 16;
+    assert!(count_a(&weighted("20", "1").unwrap()) > cells * 3 / 4);
+    assert!(count_a(&weighted("1", "20").unwrap()) < cells / 4);
+}
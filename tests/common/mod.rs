@@ -0,0 +1,15 @@
+use wfc_tiles::{AdjacencyMap, Direction, WFCAdjacencyMap};
+
+/// A tileset where tile `a` (drawn `A`) allows only `b` (drawn `B`), and only in `direction`,
+/// and `b` allows only `a` back the other way.
+pub fn pair(direction: Direction) -> AdjacencyMap {
+    let source = format!(
+        r#"{{
+            "a": {{ "char": "A", "valid_neighbors": {{ "{}": ["b"] }} }},
+            "b": {{ "char": "B", "valid_neighbors": {{ "{}": ["a"] }} }}
+        }}"#,
+        direction,
+        direction.opposite()
+    );
+    AdjacencyMap::parse(&source).unwrap()
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use common::pair;
+use wfc_tiles::{AdjacencyMap, Board, CollapseOptions, Direction, Tile};
+
+fn solve(prototype_map: &AdjacencyMap, width: isize, height: isize) -> Board {
+    let mut board = Board::create(prototype_map, width, height);
+    assert!(board.collapse(&CollapseOptions::default()));
+    board
+}
+
+#[test]
+fn opposite_reverses_offset() {
+    for direction in Direction::ALL {
+        let [dx, dy, dz] = direction.offset();
+        assert_eq!(direction.opposite().offset(), [-dx, -dy, -dz]);
+        assert_eq!(direction.opposite().opposite(), direction);
+    }
+}
+
+#[test]
+fn names_round_trip() {
+    for direction in Direction::ALL {
+        assert_eq!(direction.name().parse::<Direction>(), Ok(direction));
+    }
+    assert!("north".parse::<Direction>().is_err());
+}
+
+#[test]
+fn right_of_renders_to_the_right() {
+    let board = solve(&pair(Direction::Right), 2, 1);
+    assert_eq!(board.render(), "AB\n");
+}
+
+#[test]
+fn left_of_renders_to_the_left() {
+    let board = solve(&pair(Direction::Left), 2, 1);
+    assert_eq!(board.render(), "BA\n");
+}
+
+#[test]
+fn above_renders_on_the_line_above() {
+    let board = solve(&pair(Direction::Above), 1, 2);
+    assert_eq!(board.render(), "B\nA\n");
+}
+
+#[test]
+fn below_renders_on_the_line_below() {
+    let board = solve(&pair(Direction::Below), 1, 2);
+    assert_eq!(board.render(), "A\nB\n");
+}
+
+#[test]
+fn get_uses_screen_coordinates() {
+    let board = solve(&pair(Direction::Above), 1, 2);
+    assert_eq!(board.get(&[0, 0]), Some(Tile::Collapsed("b".to_string())));
+    assert_eq!(board.get(&[0, 1]), Some(Tile::Collapsed("a".to_string())));
+    assert_eq!(board.get(&[0, 2]), None);
+}
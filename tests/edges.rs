@@ -0,0 +1,67 @@
+use wfc_tiles::{AdjacencyMap, Board, CollapseOptions, SeedError, WFCAdjacencyMap};
+
+/// Land and water fitting anywhere next to each other, plus the edge in `land_edges` for land
+/// and `water_edges` for water. Water is the heavier, so it shows up wherever it may.
+fn coast(land_edges: &str, water_edges: &str) -> AdjacencyMap {
+    let tile = |character: &str, weight: u32, edges: &str| {
+        let valid_neighbors: Vec<String> = ["right", "left", "above", "below"]
+            .iter()
+            .map(|&direction| {
+                let edge = if edges.split_whitespace().any(|edge| edge == direction) {
+                    r#", "edge""#
+                } else {
+                    ""
+                };
+                format!(r#""{}": ["land", "water"{}]"#, direction, edge)
+            })
+            .collect();
+        format!(
+            r#"{{ "char": "{}", "weight": {}, "valid_neighbors": {{ {} }} }}"#,
+            character,
+            weight,
+            valid_neighbors.join(", ")
+        )
+    };
+    AdjacencyMap::parse(&format!(
+        r#"{{ "land": {}, "water": {} }}"#,
+        tile("#", 1, land_edges),
+        tile("~", 8, water_edges)
+    ))
+    .unwrap()
+}
+
+#[test]
+fn edge_rules_restrict_only_their_side() {
+    let prototype_map = coast("left", "");
+    let mut right_column = String::new();
+    for seed in 0..4 {
+        let mut board = Board::create(&prototype_map, 6, 5);
+        assert!(board.collapse(&CollapseOptions {
+            seed,
+            ..CollapseOptions::default()
+        }));
+        for line in board.render().lines() {
+            assert!(line.starts_with('#'), "{}", line);
+            right_column.push(line.chars().last().unwrap());
+        }
+    }
+    assert!(right_column.contains('~'));
+}
+
+#[test]
+fn impossible_edge_rules_are_a_contradiction() {
+    // A one-cell-wide board is on both the left and the right edge, which no tile allows.
+    let prototype_map = coast("left", "right");
+    let mut board = Board::create(&prototype_map, 1, 3);
+    assert!(matches!(
+        board.seed(&[]),
+        Err(SeedError::EdgeContradiction { .. })
+    ));
+
+    let mut board = Board::create(&prototype_map, 1, 3);
+    assert!(!board.collapse(&CollapseOptions::default()));
+
+    let mut board = Board::create(&prototype_map, 2, 3);
+    assert!(board.collapse(&CollapseOptions::default()));
+    assert_eq!(board.render(), "#~\n#~\n#~\n");
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use common::pair;
+use wfc_tiles::{
+    AdjacencyMap, Board, CollapseOptions, Direction, Graph, Grid, TilesetError, Topology,
+    WFCAdjacencyMap,
+};
+
+#[test]
+fn adjacent_links_graph_cells_both_ways() {
+    let prototype_map = pair(Direction::Adjacent);
+
+    let mut path = Graph::new(3);
+    path.connect(0, Direction::Adjacent, 1);
+    path.connect(1, Direction::Adjacent, 2);
+    let mut board = Board::new(&prototype_map, path);
+    assert!(board.collapse(&CollapseOptions::default()));
+    let render = board.render();
+    assert!(render == "0 A\n1 B\n2 A\n" || render == "0 B\n1 A\n2 B\n");
+
+    let mut triangle = Graph::new(3);
+    triangle.connect(0, Direction::Adjacent, 1);
+    triangle.connect(1, Direction::Adjacent, 2);
+    triangle.connect(2, Direction::Adjacent, 0);
+    let mut board = Board::new(&prototype_map, triangle);
+    assert!(!board.collapse(&CollapseOptions::default()));
+}
+
+#[test]
+fn graphs_pair_their_own_labels() {
+    assert_eq!(Direction::label("right"), Direction::Right);
+    let (north, south) = (Direction::label("north"), Direction::label("south"));
+    assert_eq!(north, Direction::Label("north"));
+
+    // The hall leads down into the cellar, which leads nowhere else.
+    let prototype_map = AdjacencyMap::parse(
+        r#"{
+            "hall": { "char": "H", "valid_neighbors": { "south": ["cellar"], "north": [] } },
+            "cellar": { "char": "C", "valid_neighbors": { "north": ["hall"], "south": [] } }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(prototype_map.grid(), Grid::Graph);
+
+    let mut stairs = Graph::new(2);
+    stairs.pair(north, south);
+    stairs.connect(0, south, 1);
+    assert_eq!(stairs.opposite(south), north);
+    let mut board = Board::new(&prototype_map, stairs);
+    assert!(board.collapse(&CollapseOptions::default()));
+    assert_eq!(board.render(), "0 H\n1 C\n");
+
+    let mut stairs = Graph::new(3);
+    stairs.pair(north, south);
+    stairs.connect(0, south, 1);
+    stairs.connect(1, south, 2);
+    let mut board = Board::new(&prototype_map, stairs);
+    assert!(!board.collapse(&CollapseOptions::default()));
+
+    // Unpaired, the cellar would have to see the hall to its south as well.
+    let mut stairs = Graph::new(2);
+    stairs.connect(0, south, 1);
+    let mut board = Board::new(&prototype_map, stairs);
+    assert!(!board.collapse(&CollapseOptions::default()));
+}
+
+#[test]
+fn labels_are_only_for_graphs() {
+    match AdjacencyMap::parse(
+        r#"{ "a": { "char": "A", "valid_neighbors": {
+            "right": ["a"], "left": ["a"], "above": ["a"], "nether": ["a"]
+        } } }"#,
+    ) {
+        Err(TilesetError::UnknownDirection {
+            path, direction, ..
+        }) => {
+            assert_eq!(path, "$.a.valid_neighbors.nether");
+            assert_eq!(direction, "nether");
+        }
+        other => panic!("{:?}", other.map(|_| ())),
+    }
+}
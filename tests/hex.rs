@@ -0,0 +1,23 @@
+mod common;
+
+use common::pair;
+use wfc_tiles::{AdjacencyMap, Board, CollapseOptions, Direction, Tile};
+
+fn solve(prototype_map: &AdjacencyMap, width: isize, height: isize) -> Board {
+    let mut board = Board::create(prototype_map, width, height);
+    assert!(board.collapse(&CollapseOptions::default()));
+    board
+}
+
+#[test]
+fn hex_below_right_renders_on_the_next_row_shifted_right() {
+    let board = solve(&pair(Direction::BelowRight), 1, 2);
+    assert_eq!(board.render(), "A\n B\n");
+    assert_eq!(board.get(&[0, 1]), Some(Tile::Collapsed("b".to_string())));
+}
+
+#[test]
+fn hex_above_left_renders_on_the_previous_row_shifted_left() {
+    let board = solve(&pair(Direction::AboveLeft), 1, 2);
+    assert_eq!(board.render(), "B\n A\n");
+}
@@ -0,0 +1,87 @@
+use wfc_tiles::{infer::InferError, AdjacencyMap, Inference, WFCAdjacencyMap};
+
+fn tileset(characters: &[(&str, &str)]) -> AdjacencyMap {
+    let tiles: Vec<String> = characters
+        .iter()
+        .map(|(name, character)| {
+            format!(
+                r#""{}": {{ "char": "{}", "valid_neighbors": {{}} }}"#,
+                name, character
+            )
+        })
+        .collect();
+    AdjacencyMap::parse_unchecked(&format!("{{ {} }}", tiles.join(", "))).unwrap()
+}
+
+#[test]
+fn infers_the_neighbors_and_weights_seen() {
+    let mut inference = Inference::new(&tileset(&[("a", "A"), ("b", "B"), ("c", "C")]));
+    inference.add("AB\nAA\n").unwrap();
+
+    let prototypes_json = inference.to_json();
+    assert_eq!(
+        prototypes_json["a"],
+        json::object! {
+            "char": "A",
+            "valid_neighbors": {
+                "right": ["a", "b"],
+                "left": ["a"],
+                "above": ["a", "b"],
+                "below": ["a"],
+            },
+            "weight": 3,
+        }
+    );
+    assert_eq!(
+        prototypes_json["b"],
+        json::object! {
+            "char": "B",
+            "valid_neighbors": { "right": [], "left": ["a"], "above": [], "below": ["a"] },
+            "weight": 1,
+        }
+    );
+    // Tiles that never appear are left out.
+    assert!(!prototypes_json.has_key("c"));
+
+    // Later examples add to the counts without repeating neighbors.
+    inference.add("BA").unwrap();
+    let prototypes_json = inference.to_json();
+    assert_eq!(prototypes_json["a"]["weight"], 4);
+    assert_eq!(
+        prototypes_json["a"]["valid_neighbors"]["left"],
+        json::array!["a", "b"]
+    );
+    assert_eq!(
+        prototypes_json["b"]["valid_neighbors"]["right"],
+        json::array!["a"]
+    );
+}
+
+#[test]
+fn rejects_unknown_characters() {
+    let mut inference = Inference::new(&tileset(&[("a", "A")]));
+    assert_eq!(
+        inference.add("AA\nA?\n"),
+        Err(InferError::UnknownCharacter {
+            row: 2,
+            column: 2,
+            character: '?',
+        })
+    );
+    assert_eq!(inference.to_json(), json::object! {});
+}
+
+#[test]
+fn rejects_ambiguous_characters() {
+    let mut inference = Inference::new(&tileset(&[("a", "A"), ("b", "B"), ("other_b", "B")]));
+    assert_eq!(
+        inference.add("AB"),
+        Err(InferError::AmbiguousCharacter {
+            row: 1,
+            column: 2,
+            character: 'B',
+            tiles: vec!["b".to_string(), "other_b".to_string()],
+        })
+    );
+    assert_eq!(inference.to_json(), json::object! {});
+}
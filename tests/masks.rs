@@ -0,0 +1,53 @@
+use wfc_tiles::{AdjacencyMap, Board, CollapseOptions, Mask, WFCAdjacencyMap};
+
+#[test]
+fn parse_treats_spaces_and_dots_as_outside() {
+    let mask = Mask::parse("#.#\n x\n\n");
+    assert_eq!((mask.width(), mask.height()), (3, 3));
+    assert!(mask.contains(0, 0));
+    assert!(!mask.contains(1, 0));
+    assert!(mask.contains(2, 0));
+    assert!(!mask.contains(0, 1));
+    assert!(mask.contains(1, 1));
+    // Short and empty rows are padded, and nothing lies outside the drawing.
+    assert!(!mask.contains(2, 1));
+    assert!(!mask.contains(0, 2));
+    assert!(!mask.contains(-1, 0));
+    assert!(!mask.contains(3, 0));
+    assert!(!mask.contains(0, 3));
+}
+
+#[test]
+fn masked_cells_act_as_the_edge_and_render_blank() {
+    // Shore may touch the edge on any side, and water never may.
+    let any = r#"["shore", "water", "edge"]"#;
+    let inland = r#"["shore", "water"]"#;
+    let prototype_map = AdjacencyMap::parse(&format!(
+        r#"{{
+            "shore": {{ "char": "s", "valid_neighbors": {{
+                "right": {any}, "left": {any}, "above": {any}, "below": {any}
+            }} }},
+            "water": {{ "char": "~", "weight": 8, "valid_neighbors": {{
+                "right": {inland}, "left": {inland}, "above": {inland}, "below": {inland}
+            }} }}
+        }}"#,
+        any = any,
+        inland = inland
+    ))
+    .unwrap();
+    let mask = Mask::parse("#####\n#####\n##.##\n#####\n#####\n");
+
+    for seed in 0..4 {
+        let mut board = Board::create(&prototype_map, 5, 5).with_mask(&mask);
+        assert!(board.collapse(&CollapseOptions {
+            seed,
+            ..CollapseOptions::default()
+        }));
+        let render = board.render();
+        let rows: Vec<Vec<char>> = render.lines().map(|line| line.chars().collect()).collect();
+        assert_eq!(rows[2][2], ' ', "{}", render);
+        for (x, y) in [(2, 1), (1, 2), (3, 2), (2, 3)] {
+            assert_eq!(rows[y][x], 's', "{}", render);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use wfc_tiles::{overlap, AdjacencyMap, Direction};
+
+fn neighbors(prototype_map: &AdjacencyMap, tile: &str, direction: Direction) -> Vec<String> {
+    let mut list = prototype_map[tile].valid_neighbors[&direction].clone();
+    list.sort();
+    list
+}
+
+#[test]
+fn learns_each_distinct_block_once() {
+    let prototype_map = overlap::learn("ab\ncd\n", 2, true);
+    assert_eq!(prototype_map.len(), 4);
+    for (name, character) in [("p0", "a"), ("p1", "b"), ("p2", "c"), ("p3", "d")] {
+        assert_eq!(prototype_map[name].character, character);
+        assert_eq!(prototype_map[name].weight, 1.0);
+    }
+}
+
+#[test]
+fn weights_count_occurrences() {
+    let prototype_map = overlap::learn("aaaa\naaab\n", 2, false);
+    assert_eq!(prototype_map.len(), 2);
+    assert_eq!(prototype_map["p0"].weight, 2.0);
+    assert_eq!(prototype_map["p1"].weight, 1.0);
+    assert_eq!(prototype_map["p1"].character, "a");
+}
+
+#[test]
+fn neighbors_agree_where_they_overlap() {
+    // p0 is ab/cd, p1 ba/dc, p2 cd/ab and p3 dc/ba.
+    let prototype_map = overlap::learn("ab\ncd\n", 2, true);
+    assert_eq!(neighbors(&prototype_map, "p0", Direction::Right), ["p1"]);
+    assert_eq!(neighbors(&prototype_map, "p0", Direction::Left), ["p1"]);
+    assert_eq!(neighbors(&prototype_map, "p0", Direction::Below), ["p2"]);
+    assert_eq!(neighbors(&prototype_map, "p0", Direction::Above), ["p2"]);
+    assert_eq!(neighbors(&prototype_map, "p3", Direction::Right), ["p2"]);
+    assert_eq!(neighbors(&prototype_map, "p3", Direction::Below), ["p1"]);
+
+    // A lone bounded block has nothing it agrees with when shifted.
+    let prototype_map = overlap::learn("ab\ncd\n", 2, false);
+    assert_eq!(prototype_map.len(), 1);
+    for direction in [
+        Direction::Right,
+        Direction::Left,
+        Direction::Above,
+        Direction::Below,
+    ] {
+        assert!(neighbors(&prototype_map, "p0", direction).is_empty());
+    }
+}
+
+#[test]
+fn periodic_samples_count_blocks_across_the_edges() {
+    // Bounded: aa/aa twice and aa/ab once. Wrapping adds aa/ba, ab/aa and ba/aa, and two
+    // more aa/aa from the wrapped rows.
+    let bounded = overlap::learn("aaaa\naaab\n", 2, false);
+    let periodic = overlap::learn("aaaa\naaab\n", 2, true);
+    assert_eq!(bounded.len(), 2);
+    assert_eq!(periodic.len(), 5);
+    assert_eq!(periodic["p0"].weight, 4.0);
+    let total: f64 = periodic.values().map(|tile| tile.weight).sum();
+    assert_eq!(total, 8.0);
+}
+
+#[test]
+fn too_small_samples_learn_nothing() {
+    assert!(overlap::learn("ab\ncd\n", 0, true).is_empty());
+    assert!(overlap::learn("ab\ncd\n", 3, false).is_empty());
+}
@@ -0,0 +1,24 @@
+mod common;
+
+use common::pair;
+use wfc_tiles::{Board, CollapseOptions, Direction, SeedError, Tile};
+
+#[test]
+fn seeds_use_screen_coordinates() {
+    let prototype_map = pair(Direction::Right);
+
+    let mut board = Board::create(&prototype_map, 2, 1);
+    board
+        .seed(&[([1, 0, 0], Tile::Collapsed("b".to_string()))])
+        .unwrap();
+    assert!(board.collapse(&CollapseOptions::default()));
+    assert_eq!(board.render(), "AB\n");
+
+    let mut board = Board::create(&prototype_map, 2, 1);
+    let result = board.seed(&[([0, 0, 0], Tile::Collapsed("b".to_string()))]);
+    assert!(matches!(result, Err(SeedError::Contradiction { .. })));
+
+    let mut board = Board::create(&prototype_map, 2, 1);
+    let result = board.seed(&[([0, 0, 0], Tile::Uncollapsed(Vec::new()))]);
+    assert!(matches!(result, Err(SeedError::EmptyDomain { .. })));
+}
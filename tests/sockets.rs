@@ -0,0 +1,74 @@
+use wfc_tiles::{AdjacencyMap, Direction, Socket, WFCAdjacencyMap};
+
+fn socket(label: &str, symmetric: bool) -> Socket {
+    Socket {
+        label: label.to_string(),
+        symmetric,
+    }
+}
+
+#[test]
+fn symmetric_label_fits_itself() {
+    let grass = socket("grass", true);
+    assert_eq!(grass.mirrored(), grass);
+    assert!(grass.fits(&grass));
+    assert!(!grass.fits(&socket("water", true)));
+
+    let prototype_map = AdjacencyMap::parse(
+        r#"{
+            "a": { "char": "A", "sockets": { "right": "grass" } },
+            "b": { "char": "B", "sockets": { "left": "grass" } }
+        }"#,
+    )
+    .unwrap();
+    assert!(prototype_map["a"].sockets[&Direction::Right].symmetric);
+    assert!(prototype_map.compatible("a", Direction::Right, "b"));
+    assert!(prototype_map.compatible("b", Direction::Left, "a"));
+}
+
+#[test]
+fn asymmetric_label_fits_only_its_mirror() {
+    let prototype_map = AdjacencyMap::parse(
+        r#"{
+            "a": { "char": "A", "sockets": { "right": "road", "left": "road" } },
+            "b": { "char": "B", "sockets": { "right": "road'", "left": "road'" } }
+        }"#,
+    )
+    .unwrap();
+    assert!(!prototype_map["a"].sockets[&Direction::Right].symmetric);
+    assert!(!prototype_map["b"].sockets[&Direction::Left].symmetric);
+    assert!(prototype_map.compatible("a", Direction::Right, "b"));
+    assert!(prototype_map.compatible("b", Direction::Right, "a"));
+    assert!(!prototype_map.compatible("a", Direction::Right, "a"));
+    assert!(!prototype_map.compatible("b", Direction::Right, "b"));
+
+    let road = socket("road", false);
+    assert!(road.fits(&socket("road'", false)));
+    assert!(!road.fits(&road));
+    assert_eq!(road.mirrored().mirrored(), road);
+}
+
+#[test]
+fn reflected_variant_has_mirrored_sockets() {
+    let prototype_map = AdjacencyMap::parse(
+        r#"{
+            "f": {
+                "char": "F",
+                "symmetry": "F",
+                "sockets": { "right": "road", "left": "grass", "above": "road'" }
+            }
+        }"#,
+    )
+    .unwrap();
+    let base = &prototype_map["f"];
+    // Variant 4 is the base tile mirrored left to right.
+    let mirrored = &prototype_map["f@4"];
+    assert_eq!(
+        mirrored.sockets[&Direction::Left],
+        base.sockets[&Direction::Right].mirrored()
+    );
+    assert_eq!(mirrored.sockets[&Direction::Left].label, "road'");
+    assert_eq!(mirrored.sockets[&Direction::Right].label, "grass");
+    assert_eq!(mirrored.sockets[&Direction::Above].label, "road");
+    assert!(!mirrored.sockets.contains_key(&Direction::Below));
+}
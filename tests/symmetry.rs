@@ -0,0 +1,139 @@
+use wfc_tiles::{symmetry::variant_name, AdjacencyMap, Direction, Symmetry, WFCAdjacencyMap};
+
+/// `variant` turned `turns` quarters counter-clockwise.
+fn rotated(symmetry: Symmetry, variant: usize, turns: usize) -> usize {
+    (0..turns).fold(variant, |variant, _| symmetry.rotate(variant))
+}
+
+#[test]
+fn tables_form_the_symmetries_of_a_square() {
+    for symmetry in Symmetry::ALL {
+        for variant in 0..symmetry.cardinality() {
+            assert!(symmetry.rotate(variant) < symmetry.cardinality());
+            assert!(symmetry.reflect(variant) < symmetry.cardinality());
+            assert_eq!(rotated(symmetry, variant, 4), variant, "{}", symmetry);
+            assert_eq!(
+                symmetry.reflect(symmetry.reflect(variant)),
+                variant,
+                "{}",
+                symmetry
+            );
+            // A mirrored tile turned one way looks like the tile turned the other way, mirrored.
+            assert_eq!(
+                symmetry.reflect(symmetry.rotate(symmetry.reflect(variant))),
+                rotated(symmetry, variant, 3),
+                "{}",
+                symmetry
+            );
+        }
+    }
+}
+
+#[test]
+fn every_variant_is_a_turn_of_the_base_tile() {
+    for symmetry in Symmetry::ALL {
+        let mut reached = vec![false; symmetry.cardinality()];
+        for reflect in [false, true] {
+            let base = if reflect { symmetry.reflect(0) } else { 0 };
+            for turns in 0..4 {
+                reached[rotated(symmetry, base, turns)] = true;
+            }
+        }
+        assert!(reached.into_iter().all(|reached| reached), "{}", symmetry);
+    }
+}
+
+#[test]
+fn tables_match_each_class() {
+    assert_eq!(Symmetry::X.rotate(0), 0);
+    assert_eq!(Symmetry::X.reflect(0), 0);
+
+    assert_eq!(Symmetry::I.rotate(0), 1);
+    assert_eq!(Symmetry::I.reflect(0), 0);
+    assert_eq!(Symmetry::I.reflect(1), 1);
+
+    assert_eq!(Symmetry::Backslash.rotate(0), 1);
+    assert_eq!(Symmetry::Backslash.reflect(0), 1);
+
+    // `╦` mirrors onto itself and `╠` onto `╣`.
+    assert_eq!(Symmetry::T.reflect(0), 0);
+    assert_eq!(Symmetry::T.reflect(1), 3);
+    assert_eq!(Symmetry::T.reflect(2), 2);
+
+    // `╚` mirrors onto `╝`, its next turn, and `╗` onto `╔`.
+    assert_eq!(Symmetry::L.reflect(0), Symmetry::L.rotate(0));
+    assert_eq!(Symmetry::L.reflect(2), 3);
+
+    let f: Vec<usize> = (0..8).map(|variant| Symmetry::F.reflect(variant)).collect();
+    assert_eq!(f, [4, 5, 6, 7, 0, 1, 2, 3]);
+    assert_eq!(Symmetry::F.rotate(3), 0);
+    assert_eq!(Symmetry::F.rotate(4), 7);
+}
+
+/// A tileset with one tile of `symmetry`, drawn `character`, whose open `sides` take a plain
+/// `pipe` tile.
+fn expand(symmetry: Symmetry, character: &str, sides: &[Direction]) -> AdjacencyMap {
+    let valid_neighbors: Vec<String> = sides
+        .iter()
+        .map(|side| format!("\"{}\": [\"pipe\"]", side))
+        .collect();
+    let pipe_sides: Vec<String> = Direction::ALL[..4]
+        .iter()
+        .map(|side| format!("\"{}\": [\"t\", \"t@1\", \"t@2\", \"t@3\"]", side))
+        .collect();
+    let source = format!(
+        r#"{{
+            "t": {{ "char": "{}", "symmetry": "{}", "valid_neighbors": {{ {} }} }},
+            "pipe": {{ "char": "+", "valid_neighbors": {{ {} }} }}
+        }}"#,
+        character,
+        symmetry,
+        valid_neighbors.join(", "),
+        pipe_sides.join(", ")
+    );
+    AdjacencyMap::parse_unchecked(&source).unwrap()
+}
+
+/// Each variant's character, and the sides it takes a pipe on, in `Direction::ALL` order.
+fn variants(prototype_map: &AdjacencyMap) -> Vec<(String, Vec<Direction>)> {
+    (0..4)
+        .map(|variant| {
+            let tile = &prototype_map[&variant_name("t", variant)];
+            let sides = Direction::ALL
+                .into_iter()
+                .filter(|&side| tile.allows(side, "pipe"))
+                .collect();
+            (tile.character.clone(), sides)
+        })
+        .collect()
+}
+
+#[test]
+fn l_variants_turn_counter_clockwise_with_their_rules() {
+    use Direction::{Above, Below, Left, Right};
+    let prototype_map = expand(Symmetry::L, "╚", &[Above, Right]);
+    assert_eq!(
+        variants(&prototype_map),
+        [
+            ("╚".to_string(), vec![Right, Above]),
+            ("╝".to_string(), vec![Left, Above]),
+            ("╗".to_string(), vec![Left, Below]),
+            ("╔".to_string(), vec![Right, Below]),
+        ]
+    );
+}
+
+#[test]
+fn t_variants_turn_counter_clockwise_with_their_rules() {
+    use Direction::{Above, Below, Left, Right};
+    let prototype_map = expand(Symmetry::T, "╦", &[Right, Left, Below]);
+    assert_eq!(
+        variants(&prototype_map),
+        [
+            ("╦".to_string(), vec![Right, Left, Below]),
+            ("╠".to_string(), vec![Right, Above, Below]),
+            ("╩".to_string(), vec![Right, Left, Above]),
+            ("╣".to_string(), vec![Left, Above, Below]),
+        ]
+    );
+}
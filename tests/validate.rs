@@ -0,0 +1,127 @@
+use wfc_tiles::{
+    validate::{self, Issue},
+    AdjacencyMap, Direction, WFCAdjacencyMap,
+};
+
+fn issues(source: &str) -> Vec<Issue> {
+    validate::validate(&AdjacencyMap::parse_unchecked(source).unwrap())
+}
+
+#[test]
+fn consistent_tileset_has_no_issues() {
+    assert_eq!(
+        issues(
+            r#"{
+                "a": { "char": "A", "valid_neighbors": {
+                    "right": ["a"], "left": ["a"], "above": ["a"], "below": ["a"]
+                } }
+            }"#
+        ),
+        []
+    );
+}
+
+#[test]
+fn reports_references_to_undefined_tiles() {
+    assert_eq!(
+        issues(
+            r#"{
+                "a": { "char": "A", "valid_neighbors": {
+                    "right": ["a", "ghost"], "left": ["a"], "above": ["a"], "below": ["a"]
+                } }
+            }"#
+        ),
+        [Issue::DanglingReference {
+            tile: "a".to_string(),
+            direction: Direction::Right,
+            reference: "ghost".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn reports_directions_without_rules() {
+    assert_eq!(
+        issues(
+            r#"{
+                "a": { "char": "A", "valid_neighbors": {
+                    "right": ["a"], "left": ["a"], "above": ["a"]
+                }, "sockets": { "below": "floor" } },
+                "b": { "char": "B", "valid_neighbors": {
+                    "right": ["b"], "left": ["b"], "above": ["b"]
+                } }
+            }"#
+        )
+        .into_iter()
+        .filter(|issue| matches!(issue, Issue::MissingDirection { .. }))
+        .collect::<Vec<_>>(),
+        [Issue::MissingDirection {
+            tile: "b".to_string(),
+            direction: Direction::Below,
+        }]
+    );
+}
+
+#[test]
+fn reports_rules_the_neighbor_does_not_mirror() {
+    assert_eq!(
+        issues(
+            r#"{
+                "a": { "char": "A", "valid_neighbors": {
+                    "right": ["a", "b"], "left": ["a"], "above": ["a"], "below": ["a"]
+                } },
+                "b": { "char": "B", "valid_neighbors": {
+                    "right": ["b"], "left": ["b"], "above": ["b"], "below": ["b"]
+                } }
+            }"#
+        ),
+        [Issue::Asymmetric {
+            tile: "a".to_string(),
+            direction: Direction::Right,
+            neighbor: "b".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn reports_tiles_that_only_fit_at_the_edge() {
+    assert_eq!(
+        issues(
+            r#"{
+                "a": { "char": "A", "valid_neighbors": {
+                    "right": ["a"], "left": ["a", "wall"], "above": ["a"], "below": ["a"]
+                } },
+                "wall": { "char": "|", "valid_neighbors": {
+                    "right": ["a", "edge"], "left": ["edge"], "above": ["wall"], "below": ["wall"]
+                } }
+            }"#
+        ),
+        [Issue::Unplaceable {
+            tile: "wall".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn symmetrize_adds_each_missing_mirror_once() {
+    let mut prototypes_json = json::parse(
+        r#"{
+            "a": { "char": "A", "valid_neighbors": { "right": ["a", "b"], "above": ["b"] } },
+            "b": { "char": "B", "valid_neighbors": { "left": ["a", "edge"] } }
+        }"#,
+    )
+    .unwrap();
+
+    // a-a to the right needs a-a to the left, and b needs a below it; b-a to the left is
+    // already mirrored, and the edge is left alone.
+    assert_eq!(validate::symmetrize(&mut prototypes_json), 2);
+    assert_eq!(
+        prototypes_json["a"]["valid_neighbors"]["left"],
+        json::array!["a"]
+    );
+    assert_eq!(
+        prototypes_json["b"]["valid_neighbors"]["below"],
+        json::array!["a"]
+    );
+    assert_eq!(validate::symmetrize(&mut prototypes_json), 0);
+}
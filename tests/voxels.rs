@@ -0,0 +1,16 @@
+mod common;
+
+use common::pair;
+use wfc_tiles::{Board, CollapseOptions, Direction, Tile};
+
+#[test]
+fn up_is_the_next_layer() {
+    let mut board = Board::create_3d(&pair(Direction::Up), 1, 1, 2);
+    assert!(board.collapse(&CollapseOptions::default()));
+    assert_eq!(board.render(), "A\n\nB\n");
+    assert_eq!(
+        board.get_voxel(&[0, 0, 1]),
+        Some(Tile::Collapsed("b".to_string()))
+    );
+    assert_eq!(board.render_voxels(), "# 1 1 2\n0 0 0 a\n0 0 1 b\n");
+}
@@ -0,0 +1,15 @@
+mod common;
+
+use common::pair;
+use wfc_tiles::{Board, CollapseOptions, Direction};
+
+#[test]
+fn wrapping_onto_itself_makes_a_cell_its_own_neighbor() {
+    let prototype_map = pair(Direction::Right);
+
+    let mut board = Board::create(&prototype_map, 1, 1).with_periodic(true, false);
+    assert!(!board.collapse(&CollapseOptions::default()));
+
+    let mut board = Board::create(&pair(Direction::Below), 1, 1).with_periodic(false, true);
+    assert!(!board.collapse(&CollapseOptions::default()));
+}